@@ -0,0 +1,104 @@
+// Builds an allowlist tree and prints its root and every proof as JSON
+// Reads one address:quota per line from stdin, e.g.
+//   cargo run --example merkle_tree < allowlist.txt
+use std::io::{stdin, BufRead};
+
+use cosmwasm_std::{to_vec, Binary};
+use serde::Serialize;
+
+use whoami_paths::merkle::{hash_pair, leaf_hash};
+use whoami_paths::msg::AllowlistProof;
+
+#[derive(Serialize)]
+struct Entry {
+    address: String,
+    allowlist: AllowlistProof, // Sent as is with MintPath
+}
+
+#[derive(Serialize)]
+struct Output {
+    root: Binary, // Set with UpdateAllowlistRoot
+    entries: Vec<Entry>,
+}
+
+fn main() {
+    let entries: Vec<(String, u32)> = stdin()
+        .lock()
+        .lines()
+        .map(|line| line.unwrap().trim().to_string())
+        .filter(|line| !line.is_empty())
+        .map(|line| {
+            let (address, quota) = line
+                .rsplit_once(':')
+                .expect("each line should be address:quota");
+            let quota = quota.parse().expect("quota should be a number");
+            (address.to_string(), quota)
+        })
+        .collect();
+
+    let tree = MerkleTree::new(
+        entries
+            .iter()
+            .map(|(address, quota)| leaf_hash(address, *quota))
+            .collect(),
+    );
+    let output = Output {
+        root: tree.root(),
+        entries: entries
+            .into_iter()
+            .enumerate()
+            .map(|(index, (address, quota))| Entry {
+                address,
+                allowlist: AllowlistProof {
+                    quota,
+                    proof: tree.proof(index),
+                },
+            })
+            .collect(),
+    };
+    println!("{}", String::from_utf8(to_vec(&output).unwrap()).unwrap());
+}
+
+// An unpaired hash is carried up to the next level as is, matching verify_proof on-chain
+struct MerkleTree {
+    levels: Vec<Vec<Vec<u8>>>,
+}
+
+impl MerkleTree {
+    fn new(leaves: Vec<Vec<u8>>) -> Self {
+        let mut levels = vec![leaves];
+        while let Some(level) = levels.last().filter(|level| level.len() > 1) {
+            let next_level = level
+                .chunks(2)
+                .map(|pair| match pair {
+                    [a, b] => hash_pair(a, b),
+                    _ => pair[0].clone(),
+                })
+                .collect();
+            levels.push(next_level);
+        }
+        MerkleTree { levels }
+    }
+
+    fn root(&self) -> Binary {
+        let root = self
+            .levels
+            .last()
+            .and_then(|level| level.first())
+            .cloned()
+            .unwrap_or_default();
+        Binary::from(root)
+    }
+
+    fn proof(&self, index: usize) -> Vec<Binary> {
+        let mut proof = vec![];
+        let mut index = index;
+        for level in &self.levels[..self.levels.len() - 1] {
+            if let Some(sibling) = level.get(index ^ 1) {
+                proof.push(Binary::from(sibling.clone()));
+            }
+            index /= 2;
+        }
+        proof
+    }
+}
@@ -4,8 +4,16 @@ use std::fs::create_dir_all;
 use cosmwasm_schema::{export_schema, remove_schemas, schema_for};
 
 use whoami_paths::msg::{
-    ExecuteMsg, InstantiateMsg, PaymentDetails, PaymentDetailsBalanceResponse,
-    PaymentDetailsResponse, QueryMsg,
+    AllowlistRootResponse, AllowlistUsageResponse, BundleDiscountsResponse, BurnedResponse,
+    CanMintResponse, CommitWindowResponse, ControllerResponse, CustodialResponse,
+    DiscountsResponse, ExecuteMsg, GatingRulesResponse, HookExecuteMsg, HooksResponse,
+    InfoResponse, InstantiateMsg, ListingResponse, ListingsResponse, MigrateMsg,
+    OraclePriceResponse, OracleQueryMsg, PathAvailableResponse, PathPricesResponse, PaymentDetails,
+    PaymentDetailsBalanceResponse, PaymentDetailsResponse, PlatformFeeResponse, QueryMsg,
+    QuotePathsResponse, QuotePriceResponse, ReceiveNftMsg, ReferralConfigResponse,
+    ReferrerStatsResponse, ReturnPolicyResponse, RoyaltyInfoResponse, RoyaltyResponse,
+    StakesResponse, StakingResponse, SubRootResponse, TotalStakedResponse, UsdPricingResponse,
+    VoucherSignerResponse,
 };
 use whoami_paths::state::Config;
 
@@ -18,8 +26,43 @@ fn main() {
     export_schema(&schema_for!(InstantiateMsg), &out_dir);
     export_schema(&schema_for!(ExecuteMsg), &out_dir);
     export_schema(&schema_for!(QueryMsg), &out_dir);
+    export_schema(&schema_for!(MigrateMsg), &out_dir);
     export_schema(&schema_for!(Config), &out_dir);
     export_schema(&schema_for!(PaymentDetails), &out_dir);
     export_schema(&schema_for!(PaymentDetailsResponse), &out_dir);
     export_schema(&schema_for!(PaymentDetailsBalanceResponse), &out_dir);
+    export_schema(&schema_for!(GatingRulesResponse), &out_dir);
+    export_schema(&schema_for!(CanMintResponse), &out_dir);
+    export_schema(&schema_for!(DiscountsResponse), &out_dir);
+    export_schema(&schema_for!(QuotePriceResponse), &out_dir);
+    export_schema(&schema_for!(BundleDiscountsResponse), &out_dir);
+    export_schema(&schema_for!(QuotePathsResponse), &out_dir);
+    export_schema(&schema_for!(CommitWindowResponse), &out_dir);
+    export_schema(&schema_for!(VoucherSignerResponse), &out_dir);
+    export_schema(&schema_for!(AllowlistRootResponse), &out_dir);
+    export_schema(&schema_for!(AllowlistUsageResponse), &out_dir);
+    export_schema(&schema_for!(UsdPricingResponse), &out_dir);
+    export_schema(&schema_for!(ListingResponse), &out_dir);
+    export_schema(&schema_for!(ListingsResponse), &out_dir);
+    export_schema(&schema_for!(RoyaltyResponse), &out_dir);
+    export_schema(&schema_for!(RoyaltyInfoResponse), &out_dir);
+    export_schema(&schema_for!(ReturnPolicyResponse), &out_dir);
+    export_schema(&schema_for!(CustodialResponse), &out_dir);
+    export_schema(&schema_for!(ControllerResponse), &out_dir);
+    export_schema(&schema_for!(StakingResponse), &out_dir);
+    export_schema(&schema_for!(TotalStakedResponse), &out_dir);
+    export_schema(&schema_for!(StakesResponse), &out_dir);
+    export_schema(&schema_for!(OracleQueryMsg), &out_dir);
+    export_schema(&schema_for!(OraclePriceResponse), &out_dir);
+    export_schema(&schema_for!(ReferralConfigResponse), &out_dir);
+    export_schema(&schema_for!(ReferrerStatsResponse), &out_dir);
+    export_schema(&schema_for!(BurnedResponse), &out_dir);
+    export_schema(&schema_for!(PlatformFeeResponse), &out_dir);
+    export_schema(&schema_for!(PathAvailableResponse), &out_dir);
+    export_schema(&schema_for!(InfoResponse), &out_dir);
+    export_schema(&schema_for!(HookExecuteMsg), &out_dir);
+    export_schema(&schema_for!(HooksResponse), &out_dir);
+    export_schema(&schema_for!(ReceiveNftMsg), &out_dir);
+    export_schema(&schema_for!(SubRootResponse), &out_dir);
+    export_schema(&schema_for!(PathPricesResponse), &out_dir);
 }
@@ -1,25 +1,71 @@
 #[cfg(not(feature = "library"))]
 use cosmwasm_std::entry_point;
 use cosmwasm_std::{
-    from_binary, to_binary, Addr, BankMsg, Binary, CosmosMsg, Deps, DepsMut, Env, MessageInfo,
-    Response, StdResult, Uint128, WasmMsg,
+    coins, from_binary, to_binary, Addr, BankMsg, Binary, CosmosMsg, Decimal, Deps, DepsMut, Empty,
+    Env, Event, MessageInfo, Order, Reply, Response, StdError, StdResult, SubMsg, SubMsgResult,
+    Uint128, WasmMsg,
 };
-use cw2::set_contract_version;
-use cw20::{BalanceResponse, Cw20ExecuteMsg, Cw20QueryMsg, Cw20ReceiveMsg, TokenInfoResponse};
-use cw721::Cw721ReceiveMsg;
+use sha2::{Digest, Sha256};
+use std::cmp::Reverse;
+
+use cw_storage_plus::Bound;
+
+use cw2::{get_contract_version, set_contract_version};
+use cw20::{
+    AllowanceResponse, BalanceResponse, Cw20ExecuteMsg, Cw20QueryMsg, Cw20ReceiveMsg,
+    TokenInfoResponse,
+};
+use cw721::{Cw721QueryMsg, Cw721ReceiveMsg, OwnerOfResponse, TokensResponse};
 use cw_utils::{must_pay, nonpayable};
 
 use crate::error::ContractError;
+use crate::merkle;
 use crate::msg::{
-    ExecuteMsg, InstantiateMsg, PaymentDetails, PaymentDetailsBalanceResponse,
-    PaymentDetailsResponse, QueryMsg, ReceiveMsg,
+    AllowlistProof, AllowlistRootResponse, AllowlistUsageResponse, BundleDiscount,
+    BundleDiscountsResponse, BurnedResponse, CanMintResponse, CommitWindow, CommitWindowResponse,
+    ControllerResponse, CustodialResponse, DiscountsResponse, ExecuteMsg, GatingRulesResponse,
+    HolderDiscount, HolderRule, HolderRuleFailure, HookExecuteMsg, HooksResponse, InfoResponse,
+    InstantiateMsg, ListingResponse, ListingsResponse, MigrateMsg, OraclePriceResponse,
+    OracleQueryMsg, PathAvailableResponse, PathPattern, PathPrice, PathPricesResponse, PathStatus,
+    PatternPrice, PaymentDetails, PaymentDetailsBalanceResponse, PaymentDetailsResponse,
+    PlatformFeeResponse, QueryMsg, QuotePathsResponse, QuotePriceResponse, ReceiveMsg,
+    ReceiveNftMsg, ReferralConfig, ReferralConfigResponse, ReferralPayout, ReferrerStatsResponse,
+    ReturnPolicy, ReturnPolicyResponse, RoyaltyInfoResponse, RoyaltyResponse, StakeResponse,
+    StakesResponse, Staking, StakingResponse, SubRootResponse, TotalStakedResponse, UsdPricing,
+    UsdPricingResponse, Voucher, VoucherSignerResponse,
+};
+use crate::state::{
+    Config, Listing, MintRecord, PlatformFeeConfig, Stake, StakingConfig, SubRoot,
+    UsdPricingConfig, ALLOWLIST_ROOT, ALLOWLIST_USAGE, BUNDLE_DISCOUNTS, BURN_SHARE, COMMITMENTS,
+    COMMIT_WINDOW, CONFIG, CONTROLLERS, CUSTODIAL, DISCOUNTS, GATING_RULES, HOOKS, LISTINGS,
+    MINTED_COUNT, MINT_RECORDS, PATH_PRICES, PATTERN_PRICES, PAYMENT_DETAILS, PENDING_MINTS,
+    PLATFORM_FEE, REFERRAL_CONFIG, REFERRAL_REWARDS_OWED, REFERRER_STATS, REQUIRE_COMMITMENT,
+    RESERVED_PATHS, RETURN_POLICY, ROYALTY_BPS, STAKER_PATHS, STAKES, STAKING, SUB_ROOTS,
+    TOTAL_BURNED, TOTAL_STAKED, USD_PRICING, USED_VOUCHER_NONCES, VOUCHER_SIGNER,
 };
-use crate::state::{Config, CONFIG, PAYMENT_DETAILS};
 
 // version info for migration info
 const CONTRACT_NAME: &str = "crates.io:whoami-paths";
 const CONTRACT_VERSION: &str = env!("CARGO_PKG_VERSION");
 
+// Each path in a batch replies with its own id, counting up from here
+const MINT_PATH_REPLY_ID: u64 = 1;
+const MAX_BATCH_SIZE: u32 = 20;
+// What whoami's ContractError::Claimed displays as when the token id exists
+const WHOAMI_CLAIMED_ERROR: &str = "token_id already claimed";
+
+// Used until the admin sets a commit window, roughly a minute to a day at 6 second blocks
+const DEFAULT_COMMIT_WINDOW: CommitWindow = CommitWindow {
+    min_delay: 10,
+    expiry: 14_400,
+};
+
+const MAX_ROYALTY_BPS: u16 = 10_000;
+
+// Settings for pagination
+const MAX_LIMIT: u32 = 30;
+const DEFAULT_LIMIT: u32 = 10;
+
 fn assert_cw20(deps: Deps, cw20_addr: &Addr) -> Result<(), ContractError> {
     let _resp: TokenInfoResponse = deps
         .querier
@@ -28,13 +74,281 @@ fn assert_cw20(deps: Deps, cw20_addr: &Addr) -> Result<(), ContractError> {
     Ok(())
 }
 
+fn assert_valid_payment_details(
+    deps: Deps,
+    payment_details: &PaymentDetails,
+) -> Result<(), ContractError> {
+    match payment_details {
+        PaymentDetails::Cw20 {
+            token_address,
+            amount,
+        } => {
+            let validated_addr = deps.api.addr_validate(token_address)?;
+            assert_cw20(deps, &validated_addr)?;
+            if amount.is_zero() {
+                return Err(ContractError::InvalidPaymentAmount {});
+            }
+        }
+        PaymentDetails::Native { denom: _, amount } => {
+            if amount.is_zero() {
+                return Err(ContractError::InvalidPaymentAmount {});
+            }
+        }
+    }
+    Ok(())
+}
+
+// Overrides have to be in the base asset so everything collected can be withdrawn together
+fn assert_valid_path_price(
+    deps: Deps,
+    payment_details: &PaymentDetails,
+) -> Result<(), ContractError> {
+    assert_valid_payment_details(deps, payment_details)?;
+    let same_asset = match (PAYMENT_DETAILS.may_load(deps.storage)?, payment_details) {
+        (
+            Some(PaymentDetails::Cw20 { token_address, .. }),
+            PaymentDetails::Cw20 {
+                token_address: price_address,
+                ..
+            },
+        ) => token_address == *price_address,
+        (
+            Some(PaymentDetails::Native { denom, .. }),
+            PaymentDetails::Native {
+                denom: price_denom, ..
+            },
+        ) => denom == *price_denom,
+        _ => false,
+    };
+    if !same_asset {
+        return Err(ContractError::InvalidPathPrice {});
+    }
+    Ok(())
+}
+
+fn pattern_matches(pattern: &PathPattern, path: &str) -> bool {
+    match pattern {
+        PathPattern::Prefix { value } => path.starts_with(value.as_str()),
+        PathPattern::Suffix { value } => path.ends_with(value.as_str()),
+        PathPattern::Contains { value } => path.contains(value.as_str()),
+    }
+}
+
+// USD pricing swaps the base amount for what the USD price is currently worth in the same asset
+fn usd_payment_details(
+    deps: Deps,
+    env: &Env,
+    payment_details: PaymentDetails,
+    usd_pricing: &UsdPricingConfig,
+) -> StdResult<PaymentDetails> {
+    let asset = match &payment_details {
+        PaymentDetails::Cw20 { token_address, .. } => token_address.clone(),
+        PaymentDetails::Native { denom, .. } => denom.clone(),
+    };
+    let oracle_price: OraclePriceResponse = deps.querier.query_wasm_smart(
+        &usd_pricing.oracle,
+        &OracleQueryMsg::Price {
+            asset: asset.clone(),
+        },
+    )?;
+    if oracle_price.price.is_zero() {
+        return Err(StdError::generic_err(format!(
+            "Oracle has no price for {}",
+            asset
+        )));
+    }
+    if env.block.time.seconds()
+        > oracle_price
+            .updated_at
+            .seconds()
+            .saturating_add(usd_pricing.max_staleness)
+    {
+        return Err(StdError::generic_err(format!(
+            "Oracle price for {} is stale",
+            asset
+        )));
+    }
+
+    // Rounded up so a mint is never undercharged
+    let exact_amount = Decimal::checked_from_ratio(
+        usd_pricing.usd_price.atomics(),
+        oracle_price.price.atomics(),
+    )
+    .map_err(|err| StdError::generic_err(err.to_string()))?;
+    let mut amount = Uint128::new(1) * exact_amount;
+    if Decimal::from_ratio(amount, 1u128) < exact_amount {
+        amount += Uint128::new(1);
+    }
+
+    Ok(match payment_details {
+        PaymentDetails::Cw20 { token_address, .. } => PaymentDetails::Cw20 {
+            token_address,
+            amount,
+        },
+        PaymentDetails::Native { denom, .. } => PaymentDetails::Native { denom, amount },
+    })
+}
+
+// An exact price for the path wins, then the first matching pattern, then the base price.
+// Also says whether the price came from the USD price, only those can drift before minting
+fn path_payment_details(
+    deps: Deps,
+    env: &Env,
+    path: &str,
+) -> StdResult<(Option<PaymentDetails>, bool)> {
+    if let Some(payment_details) = PATH_PRICES.may_load(deps.storage, path)? {
+        return Ok((Some(payment_details), false));
+    }
+    let patterns = PATTERN_PRICES.may_load(deps.storage)?.unwrap_or_default();
+    if let Some(pattern_price) = patterns
+        .into_iter()
+        .find(|pattern_price| pattern_matches(&pattern_price.pattern, path))
+    {
+        return Ok((Some(pattern_price.payment_details), false));
+    }
+    match (
+        PAYMENT_DETAILS.may_load(deps.storage)?,
+        USD_PRICING.may_load(deps.storage)?,
+    ) {
+        (Some(payment_details), Some(usd_pricing)) => Ok((
+            Some(usd_payment_details(
+                deps,
+                env,
+                payment_details,
+                &usd_pricing,
+            )?),
+            true,
+        )),
+        (payment_details, _) => Ok((payment_details, false)),
+    }
+}
+
+fn validate_holder_rule(deps: Deps, rule: HolderRule) -> Result<HolderRule, ContractError> {
+    match rule {
+        HolderRule::Cw20Balance {
+            token_address,
+            min_balance,
+        } => {
+            let validated_addr = deps.api.addr_validate(&token_address)?;
+            assert_cw20(deps, &validated_addr)?;
+            Ok(HolderRule::Cw20Balance {
+                token_address: validated_addr.to_string(),
+                min_balance,
+            })
+        }
+        HolderRule::Cw721Ownership { collection_address } => {
+            let validated_addr = deps.api.addr_validate(&collection_address)?;
+            Ok(HolderRule::Cw721Ownership {
+                collection_address: validated_addr.to_string(),
+            })
+        }
+    }
+}
+
+fn holder_rule_failure(deps: Deps, rule: &HolderRule, address: &str) -> Option<String> {
+    match rule {
+        HolderRule::Cw20Balance {
+            token_address,
+            min_balance,
+        } => {
+            let resp: StdResult<BalanceResponse> = deps.querier.query_wasm_smart(
+                token_address,
+                &Cw20QueryMsg::Balance {
+                    address: address.to_string(),
+                },
+            );
+            match resp {
+                Ok(resp) if resp.balance >= *min_balance => None,
+                Ok(resp) => Some(format!(
+                    "Balance of {} is {} but {} is required",
+                    token_address, resp.balance, min_balance
+                )),
+                Err(err) => Some(format!("Could not query {}: {}", token_address, err)),
+            }
+        }
+        HolderRule::Cw721Ownership { collection_address } => {
+            let resp: StdResult<TokensResponse> = deps.querier.query_wasm_smart(
+                collection_address,
+                &Cw721QueryMsg::Tokens {
+                    owner: address.to_string(),
+                    start_after: None,
+                    limit: Some(1),
+                },
+            );
+            match resp {
+                Ok(resp) if !resp.tokens.is_empty() => None,
+                Ok(_) => Some(format!("No tokens held in {}", collection_address)),
+                Err(err) => Some(format!("Could not query {}: {}", collection_address, err)),
+            }
+        }
+    }
+}
+
+// Returns every failed rule if the address satisfies none of them, or nothing if it can mint
+fn gating_failures(
+    deps: Deps,
+    rules: Vec<HolderRule>,
+    address: &str,
+) -> StdResult<Vec<HolderRuleFailure>> {
+    let mut failures = vec![];
+    for rule in rules {
+        match holder_rule_failure(deps, &rule, address) {
+            Some(reason) => failures.push(HolderRuleFailure { rule, reason }),
+            None => return Ok(vec![]),
+        }
+    }
+    Ok(failures)
+}
+
+// Sub-roots are gated by their own rules instead of the root's
+fn assert_can_mint(
+    deps: Deps,
+    address: &str,
+    sub_root: Option<&SubRoot>,
+) -> Result<(), ContractError> {
+    let rules = match sub_root {
+        Some(sub_root) => sub_root.gating_rules.clone(),
+        None => GATING_RULES.may_load(deps.storage)?.unwrap_or_default(),
+    };
+    if !gating_failures(deps, rules, address)?.is_empty() {
+        return Err(ContractError::MintingGated {});
+    }
+    Ok(())
+}
+
+// Paths are lowercase letters, numbers, hyphens and underscores
+fn validate_path(path: &str) -> Result<(), ContractError> {
+    let valid_char = |c: char| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '-' || c == '_';
+    if path.is_empty() || !path.chars().all(valid_char) {
+        return Err(ContractError::InvalidPath {
+            path: path.to_string(),
+        });
+    }
+    Ok(())
+}
+
+// Mint policy shared by every route under the root and the PathAvailable query: the path must be
+// a valid name here before whoami sees it, and reserved paths are left for the admin
+fn assert_path_mintable(deps: Deps, path: &str, minter: &str) -> Result<(), ContractError> {
+    validate_path(path)?;
+    let config = CONFIG.load(deps.storage)?;
+    if RESERVED_PATHS.may_load(deps.storage, path)?.is_some() && config.admin != minter {
+        // Only the admin can mint reserved paths
+        return Err(ContractError::PathReserved {
+            path: path.to_string(),
+        });
+    }
+    Ok(())
+}
+
 pub fn mint_path_msg(
     whoami_address: String,
-    contract: String, // our contract address
-    owner: String,    // the person who made the MintMsg call
+    contract: String,      // our contract address
+    owner: Option<String>, // the person who made the MintMsg call, unset to keep custody
     token_id: String,
     path: String,
-) -> StdResult<Vec<WasmMsg>> {
+    reply_id: u64,
+) -> StdResult<Vec<SubMsg>> {
     let whoami_msg = whoami::msg::ExecuteMsg::MintPath(whoami::msg::MintMsg {
         token_id: path.clone(),
         owner: contract,
@@ -56,360 +370,2920 @@ pub fn mint_path_msg(
             pgp_public_key: None,
         },
     });
-    let wasm_msg1 = WasmMsg::Execute {
-        contract_addr: whoami_address.clone(),
-        msg: to_binary(&whoami_msg)?,
-        funds: vec![],
-    };
+    // The reply always runs so the pending path is cleared whether whoami accepts it or not
+    let wasm_msg1 = SubMsg::reply_always(
+        WasmMsg::Execute {
+            contract_addr: whoami_address.clone(),
+            msg: to_binary(&whoami_msg)?,
+            funds: vec![],
+        },
+        reply_id,
+    );
 
+    let owner = match owner {
+        Some(owner) => owner,
+        None => return Ok(vec![wasm_msg1]),
+    };
     let transfer_msg = whoami::msg::ExecuteMsg::TransferNft {
         recipient: owner,
         token_id: format!("{}::{}", token_id, path),
     };
-    let wasm_msg2 = WasmMsg::Execute {
+    let wasm_msg2 = SubMsg::new(WasmMsg::Execute {
         contract_addr: whoami_address,
         msg: to_binary(&transfer_msg)?,
         funds: vec![],
-    };
+    });
 
     Ok(vec![wasm_msg1, wasm_msg2])
 }
 
-fn mint(
-    env: Env,
-    whoami_address: String,
-    token_id: String,
-    path: String,
-    address_minting_the_path: String,
-    amount_paid: Uint128,
-    amount_required: Uint128,
-) -> Result<Response, ContractError> {
-    if amount_paid != amount_required {
-        return Err(ContractError::InsufficientFunds {});
-    }
+// Works out what an address has to pay for a path, applying the best holder discount.
+// Sub-roots charge their own flat price.
+fn quote_price(
+    deps: Deps,
+    env: &Env,
+    address: &str,
+    path: &str,
+    sub_root: Option<&SubRoot>,
+) -> StdResult<QuotePriceResponse> {
+    let (payment_details, usd_priced) = match sub_root {
+        Some(sub_root) => (sub_root.payment_details.clone(), false),
+        None => path_payment_details(deps, env, path)?,
+    };
+    let base_amount = match payment_details.clone() {
+        Some(PaymentDetails::Cw20 { amount, .. }) => amount,
+        Some(PaymentDetails::Native { amount, .. }) => amount,
+        None => Uint128::zero(),
+    };
 
-    let wasm_msg = mint_path_msg(
-        whoami_address,
-        env.contract.address.to_string(),
-        address_minting_the_path,
-        token_id,
-        path,
-    )?;
-    Ok(Response::new()
-        .add_attribute("action", "mint_path")
-        .add_messages(wasm_msg))
-}
+    let mut discount = None;
+    if !base_amount.is_zero() && sub_root.is_none() {
+        // Discounts are stored highest priority first, only the first match applies
+        let discounts = DISCOUNTS.may_load(deps.storage)?.unwrap_or_default();
+        for holder_discount in discounts {
+            if holder_rule_failure(deps, &holder_discount.rule, address).is_none() {
+                discount = Some(holder_discount.discount);
+                break;
+            }
+        }
+    }
 
-#[cfg_attr(not(feature = "library"), entry_point)]
-pub fn instantiate(
-    deps: DepsMut,
-    _env: Env,
-    _info: MessageInfo,
-    msg: InstantiateMsg,
-) -> Result<Response, ContractError> {
-    set_contract_version(deps.storage, CONTRACT_NAME, CONTRACT_VERSION)?;
+    let amount = match discount {
+        Some(discount) => base_amount - base_amount * discount,
+        None => base_amount,
+    };
 
-    let whoami_address = deps.api.addr_validate(&msg.whoami_address)?;
-    let admin = deps.api.addr_validate(&msg.admin)?;
+    Ok(QuotePriceResponse {
+        payment_details,
+        base_amount,
+        discount,
+        amount,
+        usd_priced,
+    })
+}
 
-    let config = Config {
-        whoami_address: whoami_address.to_string(),
-        admin: admin.clone(),
-        token_id: None,
+// Bundle discounts only apply under the root and stack on top of any holder discount
+fn quote_paths(
+    deps: Deps,
+    env: &Env,
+    address: &str,
+    paths: &[String],
+    sub_root: Option<&SubRoot>,
+) -> StdResult<QuotePathsResponse> {
+    let bundle_discount = match sub_root {
+        Some(_) => None,
+        None => BUNDLE_DISCOUNTS
+            .may_load(deps.storage)?
+            .unwrap_or_default()
+            .into_iter()
+            .find(|bundle_discount| bundle_discount.min_quantity as usize <= paths.len())
+            .map(|bundle_discount| bundle_discount.discount),
     };
 
-    CONFIG.save(deps.storage, &config)?;
-
-    if let Some(payment_details) = msg.payment_details {
-        match payment_details.clone() {
-            PaymentDetails::Cw20 {
-                token_address,
-                amount,
-            } => {
-                let validated_addr = deps.api.addr_validate(&token_address)?;
-                assert_cw20(deps.as_ref(), &validated_addr)?;
-                if amount.is_zero() {
-                    return Err(ContractError::InvalidPaymentAmount {});
-                }
-            }
-            PaymentDetails::Native { denom: _, amount } => {
-                if amount.is_zero() {
-                    return Err(ContractError::InvalidPaymentAmount {});
-                }
-            }
+    let mut quotes = vec![];
+    let mut amount = Uint128::zero();
+    for path in paths {
+        let mut quote = quote_price(deps, env, address, path, sub_root)?;
+        if let Some(bundle_discount) = bundle_discount {
+            quote.amount = quote.amount - quote.amount * bundle_discount;
         }
-        PAYMENT_DETAILS.save(deps.storage, &payment_details)?;
+        amount += quote.amount;
+        quotes.push(quote);
     }
 
-    Ok(Response::new()
-        .add_attribute("action", "instantiate")
-        .add_attribute("admin", admin.to_string()))
+    Ok(QuotePathsResponse {
+        quotes,
+        bundle_discount,
+        amount,
+    })
 }
 
-#[cfg_attr(not(feature = "library"), entry_point)]
-pub fn execute(
-    deps: DepsMut,
-    env: Env,
-    info: MessageInfo,
-    msg: ExecuteMsg,
-) -> Result<Response, ContractError> {
-    match msg {
-        ExecuteMsg::Receive(cw20_receive) => execute_receive_cw20(deps, env, info, cw20_receive),
-        ExecuteMsg::ReceiveNft(cw721_receive) => {
-            execute_receive_cw721(deps, env, info, cw721_receive)
+fn payment_msg(
+    payment_details: &PaymentDetails,
+    recipient: String,
+    amount: Uint128,
+) -> StdResult<CosmosMsg> {
+    match payment_details {
+        PaymentDetails::Cw20 { token_address, .. } => {
+            let transfer_msg = Cw20ExecuteMsg::Transfer { recipient, amount };
+            Ok(CosmosMsg::Wasm(WasmMsg::Execute {
+                contract_addr: token_address.clone(),
+                msg: to_binary(&transfer_msg)?,
+                funds: vec![],
+            }))
         }
-        ExecuteMsg::MintPath { path } => execute_mint_path(deps, env, info, path),
-        ExecuteMsg::UpdateAdmin { new_admin } => execute_update_admin(deps, env, info, new_admin),
-        ExecuteMsg::WithdrawRootToken {} => execute_withdraw_root_token(deps, env, info),
-        ExecuteMsg::WithdrawPayments {} => execute_withdraw_payments(deps, env, info),
+        PaymentDetails::Native { denom, .. } => Ok(CosmosMsg::Bank(BankMsg::Send {
+            to_address: recipient,
+            amount: coins(amount.u128(), denom),
+        })),
     }
 }
 
-pub fn execute_receive_cw20(
-    deps: DepsMut,
-    env: Env,
-    info: MessageInfo,
-    cw20_receive: Cw20ReceiveMsg,
-) -> Result<Response, ContractError> {
-    let payment_details = PAYMENT_DETAILS.may_load(deps.storage)?;
-    let config = CONFIG.load(deps.storage)?;
-
-    if payment_details.is_none() {
-        // We do not need to pay a CW20 to mint, use base execute route
-        return Err(ContractError::NoPaymentNeeded {});
+fn burn_msg(payment_details: &PaymentDetails, amount: Uint128) -> StdResult<CosmosMsg> {
+    match payment_details {
+        PaymentDetails::Cw20 { token_address, .. } => {
+            let burn_msg = Cw20ExecuteMsg::Burn { amount };
+            Ok(CosmosMsg::Wasm(WasmMsg::Execute {
+                contract_addr: token_address.clone(),
+                msg: to_binary(&burn_msg)?,
+                funds: vec![],
+            }))
+        }
+        PaymentDetails::Native { denom, .. } => Ok(CosmosMsg::Bank(BankMsg::Burn {
+            amount: coins(amount.u128(), denom),
+        })),
     }
+}
 
-    if config.token_id.is_none() {
-        // We have no token to mint off of
-        return Err(ContractError::NoRootToken {});
+fn price_of(payment_details: &PaymentDetails) -> Uint128 {
+    match payment_details {
+        PaymentDetails::Cw20 { amount, .. } => *amount,
+        PaymentDetails::Native { amount, .. } => *amount,
     }
-    let token_id = config.token_id.unwrap();
-
-    let payment_details = payment_details.unwrap();
+}
 
-    match payment_details {
-        PaymentDetails::Cw20 {
-            amount,
+fn with_amount(payment_details: &PaymentDetails, amount: Uint128) -> PaymentDetails {
+    match payment_details.clone() {
+        PaymentDetails::Cw20 { token_address, .. } => PaymentDetails::Cw20 {
             token_address,
-        } => {
-            if info.sender != token_address {
-                // Unrecognised token
-                return Err(ContractError::UnrecognisedToken {});
-            }
-
-            let recv_msg: ReceiveMsg = from_binary(&cw20_receive.msg)?;
+            amount,
+        },
+        PaymentDetails::Native { denom, .. } => PaymentDetails::Native { denom, amount },
+    }
+}
 
-            let path = match recv_msg {
-                ReceiveMsg::MintPath { path } => path,
-            };
+// Every handler emits a whoami_paths_* event (wasm-whoami_paths_* on chain) for indexers,
+// keys shared between events always carry the same meaning, payer is only ever who paid
+fn event(name: &str, env: &Env) -> Event {
+    Event::new(format!("whoami_paths_{}", name))
+        .add_attribute("block_time", env.block.time.seconds().to_string())
+}
 
-            mint(
-                env,
-                config.whoami_address,
-                token_id,
-                path,
-                cw20_receive.sender,
-                cw20_receive.amount,
-                amount,
-            )
+// The amount along with the denom or cw20 address it is in
+fn payment_attributes(event: Event, payment_details: &PaymentDetails, amount: Uint128) -> Event {
+    let event = event.add_attribute("amount", amount.to_string());
+    match payment_details {
+        PaymentDetails::Cw20 { token_address, .. } => {
+            event.add_attribute("cw20_address", token_address)
         }
-        // TODO: Improve error
-        PaymentDetails::Native { .. } => Err(ContractError::Unauthorized {}),
+        PaymentDetails::Native { denom, .. } => event.add_attribute("denom", denom),
     }
 }
 
-pub fn execute_receive_cw721(
-    deps: DepsMut,
-    _env: Env,
-    info: MessageInfo,
-    cw721_receive: Cw721ReceiveMsg,
-) -> Result<Response, ContractError> {
-    let mut config = CONFIG.load(deps.storage)?;
-    if config.whoami_address != info.sender {
-        // Coming from a different contract
-        return Err(ContractError::Unauthorized {});
+fn payout_name(payout: &ReferralPayout) -> &'static str {
+    match payout {
+        ReferralPayout::Forward => "forward",
+        ReferralPayout::Accrue => "accrue",
     }
+}
 
-    if config.token_id.is_some() {
-        // We already have a token
-        return Err(ContractError::ExistingRootToken {});
+// Platform fees, referral rewards and burns are all carved out of the same mint payment.
+// Any share not being changed is read from storage.
+fn assert_valid_shares(
+    deps: Deps,
+    platform_fee_rate: Option<Decimal>,
+    referral_share: Option<Decimal>,
+    burn_share: Option<Decimal>,
+) -> Result<(), ContractError> {
+    let platform_fee_rate = match platform_fee_rate {
+        Some(rate) => rate,
+        None => PLATFORM_FEE
+            .may_load(deps.storage)?
+            .map(|platform_fee| platform_fee.rate)
+            .unwrap_or_else(Decimal::zero),
+    };
+    let referral_share = match referral_share {
+        Some(share) => share,
+        None => REFERRAL_CONFIG
+            .may_load(deps.storage)?
+            .map(|referral_config| referral_config.share)
+            .unwrap_or_else(Decimal::zero),
+    };
+    let burn_share = match burn_share {
+        Some(share) => share,
+        None => BURN_SHARE
+            .may_load(deps.storage)?
+            .unwrap_or_else(Decimal::zero),
+    };
+
+    if platform_fee_rate + referral_share + burn_share > Decimal::one() {
+        return Err(ContractError::InvalidShare {});
     }
+    Ok(())
+}
 
-    if cw721_receive.sender != config.admin {
-        // Only admin can transfer in the name
-        return Err(ContractError::Unauthorized {});
+// Balance of the payment asset held by the contract, less anything still owed to referrers
+fn collected_balance(
+    deps: Deps,
+    env: &Env,
+    payment_details: &PaymentDetails,
+) -> StdResult<Uint128> {
+    let balance = match payment_details {
+        PaymentDetails::Cw20 { token_address, .. } => {
+            let resp: BalanceResponse = deps.querier.query_wasm_smart(
+                token_address,
+                &Cw20QueryMsg::Balance {
+                    address: env.contract.address.to_string(),
+                },
+            )?;
+            // Stakes share the balance but belong to their stakers
+            let staked = TOTAL_STAKED
+                .may_load(deps.storage, token_address)?
+                .unwrap_or_default();
+            resp.balance.saturating_sub(staked)
+        }
+        PaymentDetails::Native { denom, .. } => {
+            deps.querier
+                .query_balance(env.contract.address.to_string(), denom)?
+                .amount
+        }
+    };
+    let owed = REFERRAL_REWARDS_OWED
+        .may_load(deps.storage)?
+        .unwrap_or_default();
+    Ok(balance.saturating_sub(owed))
+}
+
+// Direct mints would let anyone copy a path out of a pending reveal and mint it first
+fn assert_commitment_not_required(deps: Deps) -> Result<(), ContractError> {
+    if REQUIRE_COMMITMENT
+        .may_load(deps.storage)?
+        .unwrap_or_default()
+    {
+        return Err(ContractError::CommitmentRequired {});
     }
+    Ok(())
+}
 
-    config.token_id = Some(cw721_receive.token_id);
+// Checks the reveal against a stored commitment and uses it up
+fn take_commitment(
+    deps: DepsMut,
+    env: &Env,
+    path: &str,
+    sender: &str,
+    salt: &str,
+) -> Result<(), ContractError> {
+    let sender = deps.api.addr_validate(sender)?;
+    let commitment = Sha256::digest(format!("{}:{}:{}", path, sender, salt).as_bytes()).to_vec();
+    let committed_at = COMMITMENTS
+        .may_load(deps.storage, (&sender, &commitment))?
+        .ok_or(ContractError::CommitmentNotFound {})?;
 
-    CONFIG.save(deps.storage, &config)?;
+    let commit_window = COMMIT_WINDOW
+        .may_load(deps.storage)?
+        .unwrap_or(DEFAULT_COMMIT_WINDOW);
+    let reveal_from = committed_at.saturating_add(commit_window.min_delay);
+    if env.block.height < reveal_from {
+        return Err(ContractError::RevealTooEarly {
+            height: reveal_from,
+        });
+    }
+    let expires_at = committed_at.saturating_add(commit_window.expiry);
+    if env.block.height > expires_at {
+        return Err(ContractError::CommitmentExpired { height: expires_at });
+    }
 
-    Ok(Response::new().add_attribute("action", "receive_cw721"))
+    COMMITMENTS.remove(deps.storage, (&sender, &commitment));
+    Ok(())
 }
 
-pub fn execute_mint_path(
+// Checks the voucher was signed for this sender and path, then uses up its nonce
+fn redeem_voucher(
     deps: DepsMut,
-    env: Env,
-    info: MessageInfo,
-    path: String,
-) -> Result<Response, ContractError> {
+    env: &Env,
+    sender: &str,
+    path: &str,
+    voucher: &Voucher,
+    signature: &Binary,
+) -> Result<(), ContractError> {
+    let pubkey = VOUCHER_SIGNER
+        .may_load(deps.storage)?
+        .ok_or(ContractError::NoVoucherSigner {})?;
+    let message_hash = Sha256::digest(to_binary(voucher)?.as_slice());
+    let valid = deps
+        .api
+        .secp256k1_verify(&message_hash, signature, &pubkey)
+        .map_err(|_| ContractError::InvalidVoucherSignature {})?;
+    if !valid {
+        return Err(ContractError::InvalidVoucherSignature {});
+    }
+
+    if voucher.contract_address != env.contract.address || voucher.chain_id != env.block.chain_id {
+        return Err(ContractError::VoucherNotForContract {});
+    }
+    if voucher.recipient != sender {
+        return Err(ContractError::VoucherNotForSender {});
+    }
+    if let Some(voucher_path) = &voucher.path {
+        if voucher_path != path {
+            return Err(ContractError::VoucherPathMismatch {
+                path: path.to_string(),
+            });
+        }
+    }
+    if voucher.expiry.is_expired(&env.block) {
+        return Err(ContractError::VoucherExpired {});
+    }
+    if USED_VOUCHER_NONCES
+        .may_load(deps.storage, voucher.nonce)?
+        .is_some()
+    {
+        return Err(ContractError::VoucherUsed {
+            nonce: voucher.nonce,
+        });
+    }
+
+    USED_VOUCHER_NONCES.save(deps.storage, voucher.nonce, &Empty {})?;
+    Ok(())
+}
+
+// With an allowlist root set, mints under the root need a proof and count towards its quota
+fn use_allowlist(
+    deps: DepsMut,
+    address: &str,
+    parent: &Option<String>,
+    quantity: usize,
+    allowlist: Option<AllowlistProof>,
+) -> Result<(), ContractError> {
+    let root = match ALLOWLIST_ROOT.may_load(deps.storage)? {
+        Some(root) if parent.is_none() => root,
+        _ => return Ok(()),
+    };
+    let allowlist = allowlist.ok_or(ContractError::NotOnAllowlist {})?;
+    let leaf = merkle::leaf_hash(address, allowlist.quota);
+    if !merkle::verify_proof(&root, &leaf, &allowlist.proof) {
+        return Err(ContractError::NotOnAllowlist {});
+    }
+
+    let used = ALLOWLIST_USAGE
+        .may_load(deps.storage, &leaf)?
+        .unwrap_or_default();
+    if used as usize + quantity > allowlist.quota as usize {
+        return Err(ContractError::AllowlistQuotaExceeded {
+            quota: allowlist.quota,
+        });
+    }
+    ALLOWLIST_USAGE.save(deps.storage, &leaf, &(used + quantity as u32))?;
+    Ok(())
+}
+
+// Registered sub-roots set their own price and rules, anything else mints under the root
+fn load_sub_root(
+    deps: Deps,
+    parent: Option<String>,
+) -> Result<Option<(String, SubRoot)>, ContractError> {
+    match parent {
+        Some(parent) => match SUB_ROOTS.may_load(deps.storage, &parent)? {
+            Some(sub_root) => Ok(Some((parent, sub_root))),
+            None => Err(ContractError::UnknownSubRoot { token_id: parent }),
+        },
+        None => Ok(None),
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
+fn mint(
+    mut deps: DepsMut,
+    env: Env,
+    whoami_address: String,
+    token_id: String,
+    paths: Vec<String>,
+    address_minting_the_path: String,
+    amount_paid: Uint128,
+    max_price: Option<Uint128>,
+    fixed_price: Option<Uint128>,
+    referrer: Option<String>,
+    sub_root: Option<(String, SubRoot)>,
+) -> Result<Response, ContractError> {
+    if paths.is_empty() {
+        return Err(ContractError::EmptyBatch {});
+    }
+    // A voucher's or stake's fixed price replaces any discounts and isn't quoted, so no oracle
+    // is asked. It is only ever for a single path under the root, in the root's payment asset
+    if fixed_price.is_some() && paths.len() != 1 {
+        return Err(
+            StdError::generic_err("A fixed price can only be used for a single path").into(),
+        );
+    }
+    if paths.len() > MAX_BATCH_SIZE as usize {
+        return Err(ContractError::BatchTooLarge {
+            max: MAX_BATCH_SIZE,
+        });
+    }
+    for (index, path) in paths.iter().enumerate() {
+        if paths[..index].contains(path) {
+            return Err(ContractError::DuplicatePath { path: path.clone() });
+        }
+        // Reserved paths only apply directly under the root
+        match &sub_root {
+            Some(_) => validate_path(path)?,
+            None => assert_path_mintable(deps.as_ref(), path, &address_minting_the_path)?,
+        }
+    }
+
+    let mut quote = match fixed_price {
+        Some(fixed_price) => QuotePathsResponse {
+            quotes: vec![QuotePriceResponse {
+                payment_details: PAYMENT_DETAILS
+                    .may_load(deps.storage)?
+                    .map(|payment_details| with_amount(&payment_details, fixed_price)),
+                base_amount: fixed_price,
+                discount: None,
+                amount: fixed_price,
+                usd_priced: false,
+            }],
+            bundle_discount: None,
+            amount: fixed_price,
+        },
+        None => quote_paths(
+            deps.as_ref(),
+            &env,
+            &address_minting_the_path,
+            &paths,
+            sub_root.as_ref().map(|(_, sub_root)| sub_root),
+        )?,
+    };
+    // Without a max price the exact amount is required, with one any surplus is refunded
+    let refund = match max_price {
+        Some(max_price) => {
+            if quote.amount > max_price {
+                return Err(ContractError::PriceAboveMax {
+                    price: quote.amount,
+                    max_price,
+                });
+            }
+            if amount_paid < quote.amount {
+                return Err(ContractError::InsufficientFunds {});
+            }
+            amount_paid - quote.amount
+        }
+        None => {
+            if amount_paid != quote.amount {
+                // Oracle prices move between quoting and minting, so the USD priced part of
+                // a mint accepts anything within the tolerance and what was paid becomes the price
+                let usd_amount: Uint128 = quote
+                    .quotes
+                    .iter()
+                    .filter(|path_quote| path_quote.usd_priced)
+                    .map(|path_quote| path_quote.amount)
+                    .sum();
+                let tolerance = match USD_PRICING.may_load(deps.storage)? {
+                    Some(usd_pricing) => usd_pricing.tolerance,
+                    None => Decimal::zero(),
+                };
+                let difference = if amount_paid > quote.amount {
+                    amount_paid - quote.amount
+                } else {
+                    quote.amount - amount_paid
+                };
+                if difference > usd_amount * tolerance {
+                    return Err(ContractError::InsufficientFunds {});
+                }
+                // The difference goes on the last USD priced path so the paths still add up
+                let last_quote = quote
+                    .quotes
+                    .iter_mut()
+                    .rev()
+                    .find(|path_quote| path_quote.usd_priced)
+                    .unwrap();
+                last_quote.amount = (last_quote.amount + amount_paid)
+                    .checked_sub(quote.amount)
+                    .map_err(|_| ContractError::InsufficientFunds {})?;
+                quote.amount = amount_paid;
+            }
+            Uint128::zero()
+        }
+    };
+
+    let mut response = Response::new().add_attribute("action", "mint_path");
+    if let Some(payment_details) = &quote.quotes[0].payment_details {
+        if !refund.is_zero() {
+            let refund_event = event("refund", &env)
+                .add_attribute("payer", env.contract.address.as_str())
+                .add_attribute("recipient", &address_minting_the_path);
+            response = response
+                .add_attribute("refund", refund.to_string())
+                .add_event(payment_attributes(refund_event, payment_details, refund))
+                .add_message(payment_msg(
+                    payment_details,
+                    address_minting_the_path.clone(),
+                    refund,
+                )?);
+        }
+    }
+    if let Some(bundle_discount) = quote.bundle_discount {
+        response = response
+            .add_attribute("bundle_discount", bundle_discount.to_string())
+            .add_attribute("paths", paths.len().to_string());
+    }
+    for (index, (path, path_quote)) in paths.into_iter().zip(quote.quotes).enumerate() {
+        let path_response = mint_one_path(
+            deps.branch(),
+            &env,
+            whoami_address.clone(),
+            token_id.clone(),
+            path,
+            address_minting_the_path.clone(),
+            path_quote,
+            quote.bundle_discount,
+            referrer.clone(),
+            sub_root.as_ref(),
+            MINT_PATH_REPLY_ID + index as u64,
+        )?;
+        response = response
+            .add_attributes(path_response.attributes)
+            .add_events(path_response.events)
+            .add_submessages(path_response.messages);
+    }
+    Ok(response)
+}
+
+// Pays out and mints a single path of an already paid for batch
+#[allow(clippy::too_many_arguments)]
+fn mint_one_path(
+    deps: DepsMut,
+    env: &Env,
+    whoami_address: String,
+    token_id: String,
+    path: String,
+    address_minting_the_path: String,
+    quote: QuotePriceResponse,
+    bundle_discount: Option<Decimal>,
+    referrer: Option<String>,
+    sub_root: Option<&(String, SubRoot)>,
+    reply_id: u64,
+) -> Result<Response, ContractError> {
+    let mut response = Response::new();
+    if let Some(discount) = quote.discount {
+        response = response
+            .add_attribute("discount", discount.to_string())
+            .add_attribute("amount", quote.amount.to_string());
+    }
+
+    let parent_token_id = match &sub_root {
+        Some((parent, _)) => parent.clone(),
+        None => token_id.clone(),
+    };
+    let full_token_id = format!("{}::{}", parent_token_id, path);
+    // The path below the root, e.g. team::alice for root::team::alice
+    let root_path = full_token_id
+        .strip_prefix(&format!("{}::", token_id))
+        .unwrap_or(&full_token_id)
+        .to_string();
+    // In custodial mode the path stays here and the minter becomes its controller
+    let custodial = CUSTODIAL.may_load(deps.storage)?.unwrap_or_default();
+    let holder = if custodial {
+        env.contract.address.to_string()
+    } else {
+        address_minting_the_path.clone()
+    };
+    let mut mint_event = event("mint", env)
+        .add_attribute("root", &token_id)
+        .add_attribute("parent", &parent_token_id)
+        .add_attribute("path", &root_path)
+        .add_attribute("token_id", &full_token_id)
+        .add_attribute("payer", &address_minting_the_path)
+        .add_attribute("recipient", &holder);
+    mint_event = match &quote.payment_details {
+        Some(payment_details) => payment_attributes(mint_event, payment_details, quote.amount),
+        None => mint_event.add_attribute("amount", Uint128::zero().to_string()),
+    };
+    if quote.discount.is_some() || bundle_discount.is_some() {
+        mint_event = mint_event.add_attribute("base_amount", quote.base_amount.to_string());
+    }
+    if let Some(discount) = quote.discount {
+        mint_event = mint_event.add_attribute("discount", discount.to_string());
+    }
+    if let Some(bundle_discount) = bundle_discount {
+        mint_event = mint_event.add_attribute("bundle_discount", bundle_discount.to_string());
+    }
+
+    let mut fee_amount = Uint128::zero();
+    if let (Some(platform_fee), Some(payment_details)) =
+        (PLATFORM_FEE.may_load(deps.storage)?, &quote.payment_details)
+    {
+        fee_amount = quote.amount * platform_fee.rate;
+        if !fee_amount.is_zero() {
+            let fee_event = event("platform_fee", env)
+                .add_attribute("token_id", &full_token_id)
+                .add_attribute("payer", &address_minting_the_path)
+                .add_attribute("recipient", platform_fee.fee_collector.as_str());
+            response = response
+                .add_attribute("platform_fee", fee_amount.to_string())
+                .add_event(payment_attributes(fee_event, payment_details, fee_amount))
+                .add_message(payment_msg(
+                    payment_details,
+                    platform_fee.fee_collector.to_string(),
+                    fee_amount,
+                )?);
+        }
+    }
+
+    // Everything after the platform fee belongs to the sub-root owner, so referral rewards
+    // and burns only come out of mints directly under the root
+    let (referral_config, burn_share) = match &sub_root {
+        Some((_, sub_root)) => {
+            if let Some(payment_details) = &quote.payment_details {
+                let owner_amount = quote.amount - fee_amount;
+                if !owner_amount.is_zero() {
+                    let owner_event = event("sub_root_payment", env)
+                        .add_attribute("token_id", &full_token_id)
+                        .add_attribute("payer", &address_minting_the_path)
+                        .add_attribute("recipient", sub_root.owner.as_str());
+                    response = response
+                        .add_event(payment_attributes(
+                            owner_event,
+                            payment_details,
+                            owner_amount,
+                        ))
+                        .add_message(payment_msg(
+                            payment_details,
+                            sub_root.owner.to_string(),
+                            owner_amount,
+                        )?);
+                }
+            }
+            (None, None)
+        }
+        None => (
+            REFERRAL_CONFIG.may_load(deps.storage)?,
+            BURN_SHARE.may_load(deps.storage)?,
+        ),
+    };
+
+    // Accrued rewards stay here but are owed, so they count as leaving along with the rest
+    let mut reward_amount = Uint128::zero();
+    if let Some(referrer) = referrer {
+        let referrer = deps.api.addr_validate(&referrer)?;
+        if referrer == address_minting_the_path {
+            return Err(ContractError::SelfReferral {});
+        }
+
+        let mut stats = REFERRER_STATS
+            .may_load(deps.storage, &referrer)?
+            .unwrap_or_default();
+        stats.referrals += 1;
+
+        if let (Some(referral_config), Some(payment_details)) =
+            (referral_config, &quote.payment_details)
+        {
+            let reward = quote.amount * referral_config.share;
+            reward_amount = reward;
+            if !reward.is_zero() {
+                let reward_event = event("referral_reward", env)
+                    .add_attribute("token_id", &full_token_id)
+                    .add_attribute("payer", &address_minting_the_path)
+                    .add_attribute("recipient", referrer.as_str());
+                response = response.add_event(
+                    payment_attributes(reward_event, payment_details, reward)
+                        .add_attribute("payout", payout_name(&referral_config.payout)),
+                );
+                stats.total_earned += reward;
+                match referral_config.payout {
+                    ReferralPayout::Forward => {
+                        response = response.add_message(payment_msg(
+                            payment_details,
+                            referrer.to_string(),
+                            reward,
+                        )?);
+                    }
+                    ReferralPayout::Accrue => {
+                        stats.claimable += reward;
+                        let owed = REFERRAL_REWARDS_OWED
+                            .may_load(deps.storage)?
+                            .unwrap_or_default();
+                        REFERRAL_REWARDS_OWED.save(deps.storage, &(owed + reward))?;
+                    }
+                }
+                response = response.add_attribute("referral_reward", reward.to_string());
+            }
+        }
+
+        REFERRER_STATS.save(deps.storage, &referrer, &stats)?;
+        response = response.add_attribute("referrer", referrer.to_string());
+        mint_event = mint_event.add_attribute("referrer", referrer.as_str());
+    }
+
+    let mut burn_amount = Uint128::zero();
+    if let (Some(burn_share), Some(payment_details)) = (burn_share, &quote.payment_details) {
+        burn_amount = quote.amount * burn_share;
+        if !burn_amount.is_zero() {
+            let total_burned = TOTAL_BURNED.may_load(deps.storage)?.unwrap_or_default();
+            TOTAL_BURNED.save(deps.storage, &(total_burned + burn_amount))?;
+            let burn_event = event("burn", env)
+                .add_attribute("token_id", &full_token_id)
+                .add_attribute("payer", &address_minting_the_path);
+            response = response
+                .add_attribute("burned", burn_amount.to_string())
+                .add_event(payment_attributes(burn_event, payment_details, burn_amount))
+                .add_message(burn_msg(payment_details, burn_amount)?);
+        }
+    }
+
+    let minted_count = MINTED_COUNT.may_load(deps.storage)?.unwrap_or_default();
+    MINTED_COUNT.save(deps.storage, &(minted_count + 1))?;
+
+    let minter = deps.api.addr_validate(&address_minting_the_path)?;
+    MINT_RECORDS.save(
+        deps.storage,
+        &full_token_id,
+        &MintRecord {
+            minter: minter.clone(),
+            parent: parent_token_id.clone(),
+            height: env.block.height,
+            price: match (&quote.payment_details, sub_root) {
+                (Some(payment_details), None) if !quote.amount.is_zero() => {
+                    Some(with_amount(payment_details, quote.amount))
+                }
+                _ => None,
+            },
+            kept: match sub_root {
+                Some(_) => Uint128::zero(),
+                None => quote
+                    .amount
+                    .saturating_sub(fee_amount + reward_amount + burn_amount),
+            },
+        },
+    )?;
+
+    if custodial {
+        CONTROLLERS.save(deps.storage, &full_token_id, &minter)?;
+        mint_event = mint_event
+            .add_attribute("custodial", "true")
+            .add_attribute("controller", minter.as_str());
+    }
+
+    PENDING_MINTS.save(deps.storage, reply_id, &path)?;
+
+    let hook_msg = to_binary(&HookExecuteMsg::PathMintedHook {
+        root: token_id,
+        path: root_path,
+        owner: holder,
+        controller: if custodial {
+            Some(minter.to_string())
+        } else {
+            None
+        },
+        amount: quote.amount,
+    })?;
+    // Hooks run after the mint and transfer so they only hear about paths that exist
+    let hook_msgs = HOOKS
+        .may_load(deps.storage)?
+        .unwrap_or_default()
+        .into_iter()
+        .map(|hook| WasmMsg::Execute {
+            contract_addr: hook.to_string(),
+            msg: hook_msg.clone(),
+            funds: vec![],
+        });
+
+    let wasm_msg = mint_path_msg(
+        whoami_address,
+        env.contract.address.to_string(),
+        if custodial {
+            None
+        } else {
+            Some(address_minting_the_path)
+        },
+        parent_token_id,
+        path,
+        reply_id,
+    )?;
+    Ok(response
+        .add_event(mint_event)
+        .add_submessages(wasm_msg)
+        .add_messages(hook_msgs))
+}
+
+// whoami only gives back an error string, pick out the failures a minter can act on.
+// Invalid names never reach whoami since they're rejected before minting
+fn mint_path_error(path: String, error: String) -> ContractError {
+    if error.contains(WHOAMI_CLAIMED_ERROR) {
+        ContractError::PathTaken { path }
+    } else {
+        ContractError::MintFailed {
+            path,
+            reason: error,
+        }
+    }
+}
+
+#[cfg_attr(not(feature = "library"), entry_point)]
+pub fn instantiate(
+    deps: DepsMut,
+    env: Env,
+    _info: MessageInfo,
+    msg: InstantiateMsg,
+) -> Result<Response, ContractError> {
+    set_contract_version(deps.storage, CONTRACT_NAME, CONTRACT_VERSION)?;
+
+    let whoami_address = deps.api.addr_validate(&msg.whoami_address)?;
+    let admin = deps.api.addr_validate(&msg.admin)?;
+
+    let config = Config {
+        whoami_address: whoami_address.to_string(),
+        admin: admin.clone(),
+        token_id: None,
+    };
+
+    CONFIG.save(deps.storage, &config)?;
+
+    if let Some(payment_details) = msg.payment_details {
+        assert_valid_payment_details(deps.as_ref(), &payment_details)?;
+        PAYMENT_DETAILS.save(deps.storage, &payment_details)?;
+    }
+
+    if let Some(platform_fee) = msg.platform_fee {
+        let fee_collector = deps.api.addr_validate(&platform_fee.fee_collector)?;
+        assert_valid_shares(deps.as_ref(), Some(platform_fee.rate), None, None)?;
+        PLATFORM_FEE.save(
+            deps.storage,
+            &PlatformFeeConfig {
+                fee_collector,
+                rate: platform_fee.rate,
+            },
+        )?;
+    }
+
+    Ok(Response::new()
+        .add_attribute("action", "instantiate")
+        .add_attribute("admin", admin.to_string())
+        .add_event(
+            event("instantiate", &env)
+                .add_attribute("admin", admin.as_str())
+                .add_attribute("whoami_address", whoami_address.as_str()),
+        ))
+}
+
+#[cfg_attr(not(feature = "library"), entry_point)]
+pub fn execute(
+    mut deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    msg: ExecuteMsg,
+) -> Result<Response, ContractError> {
+    match msg {
+        ExecuteMsg::Receive(cw20_receive) => execute_receive_cw20(deps, env, info, cw20_receive),
+        ExecuteMsg::ReceiveNft(cw721_receive) => {
+            execute_receive_cw721(deps, env, info, cw721_receive)
+        }
+        ExecuteMsg::MintPath {
+            path,
+            referrer,
+            parent,
+            max_price,
+            allowlist,
+        } => {
+            assert_commitment_not_required(deps.as_ref())?;
+            use_allowlist(deps.branch(), info.sender.as_str(), &parent, 1, allowlist)?;
+            execute_mint_path(
+                deps,
+                env,
+                info,
+                vec![path],
+                referrer,
+                parent,
+                max_price,
+                None,
+            )
+        }
+        ExecuteMsg::MintPaths {
+            paths,
+            referrer,
+            parent,
+            max_price,
+            allowlist,
+        } => {
+            assert_commitment_not_required(deps.as_ref())?;
+            use_allowlist(
+                deps.branch(),
+                info.sender.as_str(),
+                &parent,
+                paths.len(),
+                allowlist,
+            )?;
+            execute_mint_path(deps, env, info, paths, referrer, parent, max_price, None)
+        }
+        ExecuteMsg::CommitPath { commitment } => execute_commit_path(deps, env, info, commitment),
+        ExecuteMsg::RevealPath {
+            path,
+            salt,
+            referrer,
+            parent,
+            max_price,
+            allowlist,
+        } => {
+            take_commitment(deps.branch(), &env, &path, info.sender.as_str(), &salt)?;
+            use_allowlist(deps.branch(), info.sender.as_str(), &parent, 1, allowlist)?;
+            execute_mint_path(
+                deps,
+                env,
+                info,
+                vec![path],
+                referrer,
+                parent,
+                max_price,
+                None,
+            )
+        }
+        ExecuteMsg::UpdateCommitWindow { commit_window } => {
+            execute_update_commit_window(deps, env, info, commit_window)
+        }
+        ExecuteMsg::UpdateRequireCommitment { require_commitment } => {
+            execute_update_require_commitment(deps, env, info, require_commitment)
+        }
+        ExecuteMsg::MintWithVoucher {
+            path,
+            voucher,
+            signature,
+        } => {
+            redeem_voucher(
+                deps.branch(),
+                &env,
+                info.sender.as_str(),
+                &path,
+                &voucher,
+                &signature,
+            )?;
+            execute_mint_path(
+                deps,
+                env,
+                info,
+                vec![path],
+                None,
+                None,
+                None,
+                Some(voucher.price),
+            )
+        }
+        ExecuteMsg::UpdateVoucherSigner { pubkey } => {
+            execute_update_voucher_signer(deps, env, info, pubkey)
+        }
+        ExecuteMsg::UpdateAllowlistRoot { root } => {
+            execute_update_allowlist_root(deps, env, info, root)
+        }
+        ExecuteMsg::UpdateUsdPricing { usd_pricing } => {
+            execute_update_usd_pricing(deps, env, info, usd_pricing)
+        }
+        ExecuteMsg::BuyPath { token_id } => execute_buy_path(deps, env, info, token_id),
+        ExecuteMsg::CancelListing { token_id } => execute_cancel_listing(deps, env, info, token_id),
+        ExecuteMsg::UpdateRoyalty { royalty_bps } => {
+            execute_update_royalty(deps, env, info, royalty_bps)
+        }
+        ExecuteMsg::UpdateReturnPolicy { return_policy } => {
+            execute_update_return_policy(deps, env, info, return_policy)
+        }
+        ExecuteMsg::UpdateCustodial { custodial } => {
+            execute_update_custodial(deps, env, info, custodial)
+        }
+        ExecuteMsg::UpdatePathMetadata { token_id, metadata } => {
+            execute_update_path_metadata(deps, env, info, token_id, metadata)
+        }
+        ExecuteMsg::UpdateController {
+            token_id,
+            controller,
+        } => execute_update_controller(deps, env, info, token_id, controller),
+        ExecuteMsg::RevokePath { token_id } => execute_revoke_path(deps, env, info, token_id),
+        ExecuteMsg::UpdateStaking { staking } => execute_update_staking(deps, env, info, staking),
+        ExecuteMsg::Unstake { token_id } => {
+            // Paths outside custody have to be handed back to be unstaked
+            load_controller(deps.as_ref(), &token_id)?;
+            execute_unstake(deps, env, info.sender.to_string(), token_id)
+        }
+        ExecuteMsg::ClaimStake { token_id } => execute_claim_stake(deps, env, info, token_id),
+        ExecuteMsg::UpdateAdmin { new_admin } => execute_update_admin(deps, env, info, new_admin),
+        ExecuteMsg::WithdrawRootToken {} => execute_withdraw_root_token(deps, env, info),
+        ExecuteMsg::WithdrawPayments {} => execute_withdraw_payments(deps, env, info),
+        ExecuteMsg::UpdateGatingRules { rules } => {
+            execute_update_gating_rules(deps, env, info, rules)
+        }
+        ExecuteMsg::UpdateDiscounts { discounts } => {
+            execute_update_discounts(deps, env, info, discounts)
+        }
+        ExecuteMsg::UpdateBundleDiscounts { bundle_discounts } => {
+            execute_update_bundle_discounts(deps, env, info, bundle_discounts)
+        }
+        ExecuteMsg::UpdateReferralConfig { referral_config } => {
+            execute_update_referral_config(deps, env, info, referral_config)
+        }
+        ExecuteMsg::ClaimReferralRewards {} => execute_claim_referral_rewards(deps, env, info),
+        ExecuteMsg::UpdateBurnShare { burn_share } => {
+            execute_update_burn_share(deps, env, info, burn_share)
+        }
+        ExecuteMsg::UpdatePlatformFee {
+            fee_collector,
+            rate,
+        } => execute_update_platform_fee(deps, env, info, fee_collector, rate),
+        ExecuteMsg::UpdateReservedPaths { add, remove } => {
+            execute_update_reserved_paths(deps, env, info, add, remove)
+        }
+        ExecuteMsg::UpdatePathPrices { set, remove } => {
+            execute_update_path_prices(deps, env, info, set, remove)
+        }
+        ExecuteMsg::UpdatePatternPrices { patterns } => {
+            execute_update_pattern_prices(deps, env, info, patterns)
+        }
+        ExecuteMsg::UpdateSubRoot {
+            token_id,
+            payment_details,
+            gating_rules,
+        } => execute_update_sub_root(deps, env, info, token_id, payment_details, gating_rules),
+        ExecuteMsg::WithdrawSubRoot { token_id } => {
+            execute_withdraw_sub_root(deps, env, info, token_id)
+        }
+        ExecuteMsg::AddHook { addr } => execute_add_hook(deps, env, info, addr),
+        ExecuteMsg::RemoveHook { addr } => execute_remove_hook(deps, env, info, addr),
+    }
+}
+
+pub fn execute_receive_cw20(
+    mut deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    cw20_receive: Cw20ReceiveMsg,
+) -> Result<Response, ContractError> {
+    let recv_msg: ReceiveMsg = from_binary(&cw20_receive.msg)?;
+    let (paths, referrer, parent, max_price, allowlist, voucher_price) = match recv_msg {
+        ReceiveMsg::MintPath {
+            path,
+            referrer,
+            parent,
+            max_price,
+            allowlist,
+        } => {
+            assert_commitment_not_required(deps.as_ref())?;
+            (vec![path], referrer, parent, max_price, allowlist, None)
+        }
+        ReceiveMsg::MintPaths {
+            paths,
+            referrer,
+            parent,
+            max_price,
+            allowlist,
+        } => {
+            assert_commitment_not_required(deps.as_ref())?;
+            (paths, referrer, parent, max_price, allowlist, None)
+        }
+        ReceiveMsg::RevealPath {
+            path,
+            salt,
+            referrer,
+            parent,
+            max_price,
+            allowlist,
+        } => {
+            take_commitment(deps.branch(), &env, &path, &cw20_receive.sender, &salt)?;
+            (vec![path], referrer, parent, max_price, allowlist, None)
+        }
+        ReceiveMsg::MintWithVoucher {
+            path,
+            voucher,
+            signature,
+        } => {
+            redeem_voucher(
+                deps.branch(),
+                &env,
+                &cw20_receive.sender,
+                &path,
+                &voucher,
+                &signature,
+            )?;
+            (vec![path], None, None, None, None, Some(voucher.price))
+        }
+        ReceiveMsg::StakeForPath { path, allowlist } => {
+            // Staking mints under the root like any other path, so it takes an allowlist spot
+            assert_commitment_not_required(deps.as_ref())?;
+            use_allowlist(deps.branch(), &cw20_receive.sender, &None, 1, allowlist)?;
+            return execute_stake_for_path(
+                deps,
+                env,
+                info,
+                cw20_receive.sender,
+                path,
+                cw20_receive.amount,
+            );
+        }
+        ReceiveMsg::BuyPath { token_id } => {
+            let listing = load_listing(deps.as_ref(), &token_id)?;
+            match &listing.price {
+                PaymentDetails::Cw20 { token_address, .. } if info.sender == *token_address => {}
+                _ => return Err(ContractError::UnrecognisedToken {}),
+            }
+            return buy_path(
+                deps,
+                env,
+                cw20_receive.sender,
+                token_id,
+                listing,
+                cw20_receive.amount,
+            );
+        }
+    };
+    // Vouchers are signed for their recipient so don't need the allowlist as well
+    if voucher_price.is_none() {
+        use_allowlist(
+            deps.branch(),
+            &cw20_receive.sender,
+            &parent,
+            paths.len(),
+            allowlist,
+        )?;
+    }
+
+    let sub_root = load_sub_root(deps.as_ref(), parent)?;
+    let payment_details = match &sub_root {
+        Some((_, sub_root)) => sub_root.payment_details.clone(),
+        None => PAYMENT_DETAILS.may_load(deps.storage)?,
+    };
+    let config = CONFIG.load(deps.storage)?;
+
+    if payment_details.is_none() {
+        // We do not need to pay a CW20 to mint, use base execute route
+        return Err(ContractError::NoPaymentNeeded {});
+    }
+
+    if config.token_id.is_none() {
+        // We have no token to mint off of
+        return Err(ContractError::NoRootToken {});
+    }
+    let token_id = config.token_id.unwrap();
+
+    let payment_details = payment_details.unwrap();
+
+    match payment_details {
+        PaymentDetails::Cw20 { token_address, .. } => {
+            if info.sender != token_address {
+                // Unrecognised token
+                return Err(ContractError::UnrecognisedToken {});
+            }
+
+            assert_can_mint(
+                deps.as_ref(),
+                &cw20_receive.sender,
+                sub_root.as_ref().map(|(_, sub_root)| sub_root),
+            )?;
+
+            mint(
+                deps,
+                env,
+                config.whoami_address,
+                token_id,
+                paths,
+                cw20_receive.sender,
+                cw20_receive.amount,
+                max_price,
+                voucher_price,
+                referrer,
+                sub_root,
+            )
+        }
+        // TODO: Improve error
+        PaymentDetails::Native { .. } => Err(ContractError::Unauthorized {}),
+    }
+}
+
+pub fn execute_receive_cw721(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    cw721_receive: Cw721ReceiveMsg,
+) -> Result<Response, ContractError> {
+    let mut config = CONFIG.load(deps.storage)?;
+    if config.whoami_address != info.sender {
+        // Coming from a different contract
+        return Err(ContractError::Unauthorized {});
+    }
+
+    // Until a root is set anything sent in is handled as the root whatever message it carries,
+    // after that a message from anything other than the root is a path holder opting in
+    if config.token_id.is_some() && !cw721_receive.msg.is_empty() {
+        if config.token_id.as_ref() == Some(&cw721_receive.token_id) {
+            return Err(ContractError::ExistingRootToken {});
+        }
+        return match from_binary(&cw721_receive.msg)? {
+            ReceiveNftMsg::RegisterSubRoot {
+                payment_details,
+                gating_rules,
+            } => execute_register_sub_root(
+                deps,
+                env,
+                cw721_receive.sender,
+                cw721_receive.token_id,
+                payment_details,
+                gating_rules,
+            ),
+            ReceiveNftMsg::ListForSale { price } => execute_list_for_sale(
+                deps,
+                env,
+                cw721_receive.sender,
+                cw721_receive.token_id,
+                price,
+            ),
+            ReceiveNftMsg::ReturnPath {} => {
+                execute_return_path(deps, env, cw721_receive.sender, cw721_receive.token_id)
+            }
+            ReceiveNftMsg::Unstake {} => {
+                execute_unstake(deps, env, cw721_receive.sender, cw721_receive.token_id)
+            }
+        };
+    }
+
+    if config.token_id.is_some() {
+        // We already have a token
+        return Err(ContractError::ExistingRootToken {});
+    }
+
+    if cw721_receive.sender != config.admin {
+        // Only admin can transfer in the name
+        return Err(ContractError::Unauthorized {});
+    }
+
+    config.token_id = Some(cw721_receive.token_id.clone());
+
+    CONFIG.save(deps.storage, &config)?;
+
+    Ok(Response::new()
+        .add_attribute("action", "receive_cw721")
+        .add_event(
+            event("receive_root", &env)
+                .add_attribute("root", cw721_receive.token_id)
+                .add_attribute("sender", cw721_receive.sender),
+        ))
+}
+
+#[allow(clippy::too_many_arguments)]
+pub fn execute_mint_path(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    paths: Vec<String>,
+    referrer: Option<String>,
+    parent: Option<String>,
+    max_price: Option<Uint128>,
+    voucher_price: Option<Uint128>,
+) -> Result<Response, ContractError> {
+    let config = CONFIG.load(deps.storage)?;
+    let sub_root = load_sub_root(deps.as_ref(), parent)?;
+    let payment_details = match &sub_root {
+        Some((_, sub_root)) => sub_root.payment_details.clone(),
+        // Free vouchers are claimed without paying whatever the asset
+        None if voucher_price == Some(Uint128::zero()) => None,
+        None => PAYMENT_DETAILS.may_load(deps.storage)?,
+    };
+    if config.token_id.is_none() {
+        // No token to mint off of
+        return Err(ContractError::NoRootToken {});
+    }
+    let token_id = config.token_id.unwrap();
+
+    // The voucher signer has already decided who can mint
+    if voucher_price.is_none() {
+        assert_can_mint(
+            deps.as_ref(),
+            info.sender.as_str(),
+            sub_root.as_ref().map(|(_, sub_root)| sub_root),
+        )?;
+    }
+
+    if let Some(payment_details) = payment_details {
+        match payment_details {
+            PaymentDetails::Native { denom, .. } => {
+                let paid_amount = must_pay(&info, &denom)?;
+                mint(
+                    deps,
+                    env,
+                    config.whoami_address,
+                    token_id,
+                    paths,
+                    info.sender.to_string(),
+                    paid_amount,
+                    max_price,
+                    voucher_price,
+                    referrer,
+                    sub_root,
+                )
+            }
+            // Without a Send the price is pulled from an allowance given to this contract
+            PaymentDetails::Cw20 { token_address, .. } => {
+                nonpayable(&info)?;
+                let amount = match voucher_price {
+                    Some(voucher_price) => voucher_price,
+                    None => {
+                        quote_paths(
+                            deps.as_ref(),
+                            &env,
+                            info.sender.as_str(),
+                            &paths,
+                            sub_root.as_ref().map(|(_, sub_root)| sub_root),
+                        )?
+                        .amount
+                    }
+                };
+
+                let allowance: AllowanceResponse = deps.querier.query_wasm_smart(
+                    &token_address,
+                    &Cw20QueryMsg::Allowance {
+                        owner: info.sender.to_string(),
+                        spender: env.contract.address.to_string(),
+                    },
+                )?;
+                let available = if allowance.expires.is_expired(&env.block) {
+                    Uint128::zero()
+                } else {
+                    allowance.allowance
+                };
+                if available < amount {
+                    return Err(ContractError::InsufficientAllowance {
+                        allowance: available,
+                        required: amount,
+                    });
+                }
+
+                let transfer_msg = WasmMsg::Execute {
+                    contract_addr: token_address,
+                    msg: to_binary(&Cw20ExecuteMsg::TransferFrom {
+                        owner: info.sender.to_string(),
+                        recipient: env.contract.address.to_string(),
+                        amount,
+                    })?,
+                    funds: vec![],
+                };
+                let mut response = mint(
+                    deps,
+                    env,
+                    config.whoami_address,
+                    token_id,
+                    paths,
+                    info.sender.to_string(),
+                    amount,
+                    max_price,
+                    voucher_price,
+                    referrer,
+                    sub_root,
+                )?;
+                // The payment has to arrive before any of it is paid out
+                response.messages.insert(0, SubMsg::new(transfer_msg));
+                Ok(response)
+            }
+        }
+    } else {
+        nonpayable(&info)?;
+        mint(
+            deps,
+            env,
+            config.whoami_address,
+            token_id,
+            paths,
+            info.sender.to_string(),
+            Uint128::zero(),
+            max_price,
+            voucher_price,
+            referrer,
+            sub_root,
+        )
+    }
+}
+
+pub fn execute_commit_path(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    commitment: Binary,
+) -> Result<Response, ContractError> {
+    nonpayable(&info)?;
+    if commitment.len() != 32 {
+        return Err(ContractError::InvalidCommitment {});
+    }
+
+    // Commitments are kept per sender so nobody can reset the block someone else committed at
+    COMMITMENTS.save(
+        deps.storage,
+        (&info.sender, commitment.as_slice()),
+        &env.block.height,
+    )?;
+
+    Ok(Response::new()
+        .add_attribute("action", "commit_path")
+        .add_event(
+            event("commit_path", &env)
+                .add_attribute("committer", info.sender)
+                .add_attribute("commitment", commitment.to_base64()),
+        ))
+}
+
+pub fn execute_update_commit_window(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    commit_window: CommitWindow,
+) -> Result<Response, ContractError> {
+    let config = CONFIG.load(deps.storage)?;
+    if info.sender != config.admin {
+        return Err(ContractError::Unauthorized {});
+    }
+    if commit_window.min_delay >= commit_window.expiry {
+        return Err(ContractError::InvalidCommitWindow {});
+    }
+
+    COMMIT_WINDOW.save(deps.storage, &commit_window)?;
+
+    Ok(Response::new()
+        .add_attribute("action", "update_commit_window")
+        .add_event(
+            event("update_commit_window", &env)
+                .add_attribute("min_delay", commit_window.min_delay.to_string())
+                .add_attribute("expiry", commit_window.expiry.to_string()),
+        ))
+}
+
+pub fn execute_update_require_commitment(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    require_commitment: bool,
+) -> Result<Response, ContractError> {
+    let config = CONFIG.load(deps.storage)?;
+    if info.sender != config.admin {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    REQUIRE_COMMITMENT.save(deps.storage, &require_commitment)?;
+    Ok(Response::new()
+        .add_attribute("action", "update_require_commitment")
+        .add_event(
+            event("update_require_commitment", &env)
+                .add_attribute("require_commitment", require_commitment.to_string()),
+        ))
+}
+
+pub fn execute_update_voucher_signer(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    pubkey: Option<Binary>,
+) -> Result<Response, ContractError> {
+    let config = CONFIG.load(deps.storage)?;
+    if info.sender != config.admin {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    let mut update_event = event("update_voucher_signer", &env);
+    match pubkey {
+        Some(pubkey) => {
+            if pubkey.len() != 33 && pubkey.len() != 65 {
+                return Err(ContractError::InvalidVoucherSigner {});
+            }
+            update_event = update_event.add_attribute("pubkey", pubkey.to_base64());
+            VOUCHER_SIGNER.save(deps.storage, &pubkey)?;
+        }
+        None => VOUCHER_SIGNER.remove(deps.storage),
+    }
+
+    Ok(Response::new()
+        .add_attribute("action", "update_voucher_signer")
+        .add_event(update_event))
+}
+
+pub fn execute_update_allowlist_root(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    root: Option<Binary>,
+) -> Result<Response, ContractError> {
+    let config = CONFIG.load(deps.storage)?;
+    if info.sender != config.admin {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    let mut update_event = event("update_allowlist_root", &env);
+    match root {
+        Some(root) => {
+            if root.len() != 32 {
+                return Err(ContractError::InvalidAllowlistRoot {});
+            }
+            update_event = update_event.add_attribute("root", root.to_base64());
+            ALLOWLIST_ROOT.save(deps.storage, &root)?;
+        }
+        None => ALLOWLIST_ROOT.remove(deps.storage),
+    }
+
+    Ok(Response::new()
+        .add_attribute("action", "update_allowlist_root")
+        .add_event(update_event))
+}
+
+pub fn execute_update_usd_pricing(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    usd_pricing: Option<UsdPricing>,
+) -> Result<Response, ContractError> {
+    let config = CONFIG.load(deps.storage)?;
+    if info.sender != config.admin {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    let mut update_event = event("update_usd_pricing", &env);
+    match usd_pricing {
+        Some(usd_pricing) => {
+            if PAYMENT_DETAILS.may_load(deps.storage)?.is_none() {
+                // There is no asset to price in
+                return Err(ContractError::NoPaymentNeeded {});
+            }
+            if usd_pricing.usd_price.is_zero() {
+                return Err(ContractError::InvalidUsdPrice {});
+            }
+            if usd_pricing.tolerance >= Decimal::one() {
+                return Err(ContractError::InvalidTolerance {});
+            }
+            let oracle = deps.api.addr_validate(&usd_pricing.oracle)?;
+            update_event = update_event
+                .add_attribute("usd_price", usd_pricing.usd_price.to_string())
+                .add_attribute("oracle", oracle.as_str());
+            USD_PRICING.save(
+                deps.storage,
+                &UsdPricingConfig {
+                    usd_price: usd_pricing.usd_price,
+                    oracle,
+                    tolerance: usd_pricing.tolerance,
+                    max_staleness: usd_pricing.max_staleness,
+                },
+            )?;
+        }
+        None => USD_PRICING.remove(deps.storage),
+    }
+
+    Ok(Response::new()
+        .add_attribute("action", "update_usd_pricing")
+        .add_event(update_event))
+}
+
+pub fn execute_update_path_prices(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    set: Vec<PathPrice>,
+    remove: Vec<String>,
+) -> Result<Response, ContractError> {
+    let config = CONFIG.load(deps.storage)?;
+    if info.sender != config.admin {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    for path_price in &set {
+        validate_path(&path_price.path)?;
+        assert_valid_path_price(deps.as_ref(), &path_price.payment_details)?;
+        PATH_PRICES.save(deps.storage, &path_price.path, &path_price.payment_details)?;
+    }
+    for path in &remove {
+        PATH_PRICES.remove(deps.storage, path);
+    }
+
+    let set_paths = set
+        .iter()
+        .map(|path_price| path_price.path.as_str())
+        .collect::<Vec<_>>()
+        .join(",");
+    Ok(Response::new()
+        .add_attribute("action", "update_path_prices")
+        .add_attribute("set", set.len().to_string())
+        .add_attribute("removed", remove.len().to_string())
+        .add_event(
+            event("update_path_prices", &env)
+                .add_attribute("set", set_paths)
+                .add_attribute("removed", remove.join(",")),
+        ))
+}
+
+pub fn execute_update_pattern_prices(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    patterns: Vec<PatternPrice>,
+) -> Result<Response, ContractError> {
+    let config = CONFIG.load(deps.storage)?;
+    if info.sender != config.admin {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    for pattern_price in &patterns {
+        let value = match &pattern_price.pattern {
+            PathPattern::Prefix { value } => value,
+            PathPattern::Suffix { value } => value,
+            PathPattern::Contains { value } => value,
+        };
+        validate_path(value)?;
+        assert_valid_path_price(deps.as_ref(), &pattern_price.payment_details)?;
+    }
+    PATTERN_PRICES.save(deps.storage, &patterns)?;
+
+    Ok(Response::new()
+        .add_attribute("action", "update_pattern_prices")
+        .add_attribute("patterns", patterns.len().to_string())
+        .add_event(
+            event("update_pattern_prices", &env)
+                .add_attribute("patterns", patterns.len().to_string()),
+        ))
+}
+
+// A path minted through this contract is handed over so others can mint under it
+pub fn execute_register_sub_root(
+    deps: DepsMut,
+    env: Env,
+    sender: String,
+    token_id: String,
+    payment_details: Option<PaymentDetails>,
+    gating_rules: Vec<HolderRule>,
+) -> Result<Response, ContractError> {
+    if MINT_RECORDS.may_load(deps.storage, &token_id)?.is_none() {
+        return Err(ContractError::InvalidSubRoot { token_id });
+    }
+
+    let owner = deps.api.addr_validate(&sender)?;
+    let sub_root = validate_sub_root(deps.as_ref(), owner, payment_details, gating_rules)?;
+    SUB_ROOTS.save(deps.storage, &token_id, &sub_root)?;
+
+    Ok(Response::new()
+        .add_attribute("action", "register_sub_root")
+        .add_attribute("token_id", &token_id)
+        .add_event(
+            event("register_sub_root", &env)
+                .add_attribute("token_id", token_id)
+                .add_attribute("owner", sub_root.owner.as_str()),
+        ))
+}
+
+fn validate_sub_root(
+    deps: Deps,
+    owner: Addr,
+    payment_details: Option<PaymentDetails>,
+    gating_rules: Vec<HolderRule>,
+) -> Result<SubRoot, ContractError> {
+    if let Some(payment_details) = &payment_details {
+        assert_valid_payment_details(deps, payment_details)?;
+    }
+    let gating_rules = gating_rules
+        .into_iter()
+        .map(|rule| validate_holder_rule(deps, rule))
+        .collect::<Result<Vec<_>, _>>()?;
+    Ok(SubRoot {
+        owner,
+        payment_details,
+        gating_rules,
+    })
+}
+
+fn load_owned_sub_root(
+    deps: Deps,
+    token_id: &str,
+    sender: &Addr,
+) -> Result<SubRoot, ContractError> {
+    let sub_root = SUB_ROOTS.may_load(deps.storage, token_id)?.ok_or_else(|| {
+        ContractError::UnknownSubRoot {
+            token_id: token_id.to_string(),
+        }
+    })?;
+    if sub_root.owner != *sender {
+        return Err(ContractError::Unauthorized {});
+    }
+    Ok(sub_root)
+}
+
+pub fn execute_update_sub_root(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    token_id: String,
+    payment_details: Option<PaymentDetails>,
+    gating_rules: Vec<HolderRule>,
+) -> Result<Response, ContractError> {
+    let sub_root = load_owned_sub_root(deps.as_ref(), &token_id, &info.sender)?;
+    let sub_root = validate_sub_root(deps.as_ref(), sub_root.owner, payment_details, gating_rules)?;
+    SUB_ROOTS.save(deps.storage, &token_id, &sub_root)?;
+
+    Ok(Response::new()
+        .add_attribute("action", "update_sub_root")
+        .add_attribute("token_id", &token_id)
+        .add_event(event("update_sub_root", &env).add_attribute("token_id", token_id)))
+}
+
+pub fn execute_withdraw_sub_root(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    token_id: String,
+) -> Result<Response, ContractError> {
+    let config = CONFIG.load(deps.storage)?;
+    let sub_root = load_owned_sub_root(deps.as_ref(), &token_id, &info.sender)?;
+    SUB_ROOTS.remove(deps.storage, &token_id);
+
+    let transfer_msg = whoami::msg::ExecuteMsg::TransferNft {
+        recipient: sub_root.owner.to_string(),
+        token_id: token_id.clone(),
+    };
+    let wasm_msg = WasmMsg::Execute {
+        contract_addr: config.whoami_address,
+        msg: to_binary(&transfer_msg)?,
+        funds: vec![],
+    };
+
+    Ok(Response::new()
+        .add_attribute("action", "withdraw_sub_root")
+        .add_attribute("token_id", &token_id)
+        .add_event(
+            event("withdraw_sub_root", &env)
+                .add_attribute("token_id", token_id)
+                .add_attribute("recipient", sub_root.owner.as_str()),
+        )
+        .add_message(wasm_msg))
+}
+
+pub fn execute_list_for_sale(
+    deps: DepsMut,
+    env: Env,
+    sender: String,
+    token_id: String,
+    price: Uint128,
+) -> Result<Response, ContractError> {
+    if MINT_RECORDS.may_load(deps.storage, &token_id)?.is_none() {
+        return Err(ContractError::UnknownPath { token_id });
+    }
+    if STAKES.has(deps.storage, &token_id) {
+        return Err(ContractError::PathStaked { token_id });
+    }
+    if price.is_zero() {
+        return Err(ContractError::InvalidPaymentAmount {});
+    }
+    let payment_details = match PAYMENT_DETAILS.may_load(deps.storage)? {
+        Some(payment_details) => with_amount(&payment_details, price),
+        None => return Err(ContractError::NoPaymentAsset {}),
+    };
+
+    let seller = deps.api.addr_validate(&sender)?;
+    let listing_event = event("list_path", &env)
+        .add_attribute("token_id", &token_id)
+        .add_attribute("seller", seller.as_str());
+    let listing_event = payment_attributes(listing_event, &payment_details, price);
+    LISTINGS.save(
+        deps.storage,
+        &token_id,
+        &Listing {
+            seller,
+            price: payment_details,
+        },
+    )?;
+
+    Ok(Response::new()
+        .add_attribute("action", "list_for_sale")
+        .add_attribute("token_id", token_id)
+        .add_event(listing_event))
+}
+
+pub fn execute_return_path(
+    deps: DepsMut,
+    env: Env,
+    sender: String,
+    token_id: String,
+) -> Result<Response, ContractError> {
+    let return_policy = RETURN_POLICY
+        .may_load(deps.storage)?
+        .ok_or(ContractError::ReturnsDisabled {})?;
+    let mut record = MINT_RECORDS
+        .may_load(deps.storage, &token_id)?
+        .ok_or_else(|| ContractError::UnknownPath {
+            token_id: token_id.clone(),
+        })?;
+    if record.minter != sender {
+        return Err(ContractError::Unauthorized {});
+    }
+    if STAKES.has(deps.storage, &token_id) {
+        return Err(ContractError::PathStaked { token_id });
+    }
+    let closes_at = record.height + return_policy.window;
+    if env.block.height > closes_at {
+        return Err(ContractError::ReturnWindowClosed { height: closes_at });
+    }
+
+    // Free, staked and sub-root mints left nothing with the contract to hand back
+    let price = record
+        .price
+        .take()
+        .ok_or_else(|| ContractError::NothingToRefund {
+            token_id: token_id.clone(),
+        })?;
+    // Only what the contract kept from the mint can be handed back
+    let refund = std::cmp::min(price_of(&price) * return_policy.refund_rate, record.kept);
+    if refund.is_zero() {
+        return Err(ContractError::NothingToRefund { token_id });
+    }
+    if refund > collected_balance(deps.as_ref(), &env, &price)? {
+        return Err(ContractError::RefundUnavailable { refund });
+    }
+
+    let config = CONFIG.load(deps.storage)?;
+    let return_event = payment_attributes(
+        event("return_path", &env)
+            .add_attribute("token_id", &token_id)
+            .add_attribute("recipient", &sender),
+        &price,
+        refund,
+    );
+    let response = Response::new()
+        .add_attribute("action", "return_path")
+        .add_attribute("token_id", &token_id)
+        .add_attribute("refund", refund.to_string())
+        .add_message(payment_msg(&price, sender, refund)?);
+
+    if return_policy.keep_for_resale {
+        // Relisted by the admin at the price it was minted for, and can't be returned again
+        LISTINGS.save(
+            deps.storage,
+            &token_id,
+            &Listing {
+                seller: config.admin,
+                price,
+            },
+        )?;
+        MINT_RECORDS.save(deps.storage, &token_id, &record)?;
+        return Ok(response.add_event(return_event.add_attribute("kept", "true")));
+    }
+
+    // Burning frees the path to be minted again
+    MINT_RECORDS.remove(deps.storage, &token_id);
+    let burn_msg = WasmMsg::Execute {
+        contract_addr: config.whoami_address,
+        msg: to_binary(&whoami::msg::ExecuteMsg::Burn {
+            token_id: token_id.clone(),
+        })?,
+        funds: vec![],
+    };
+    Ok(response
+        .add_event(return_event.add_attribute("kept", "false"))
+        .add_message(burn_msg))
+}
+
+pub fn execute_update_return_policy(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    return_policy: Option<ReturnPolicy>,
+) -> Result<Response, ContractError> {
+    let config = CONFIG.load(deps.storage)?;
+    if info.sender != config.admin {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    let mut update_event = event("update_return_policy", &env);
+    match return_policy {
+        Some(return_policy) => {
+            if return_policy.refund_rate > Decimal::one() {
+                return Err(ContractError::InvalidRefundRate {});
+            }
+            update_event = update_event
+                .add_attribute("window", return_policy.window.to_string())
+                .add_attribute("refund_rate", return_policy.refund_rate.to_string());
+            RETURN_POLICY.save(deps.storage, &return_policy)?;
+        }
+        None => RETURN_POLICY.remove(deps.storage),
+    }
+
+    Ok(Response::new()
+        .add_attribute("action", "update_return_policy")
+        .add_event(update_event))
+}
+
+pub fn execute_update_custodial(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    custodial: bool,
+) -> Result<Response, ContractError> {
+    let config = CONFIG.load(deps.storage)?;
+    if info.sender != config.admin {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    // Only changes paths minted from now on, paths already in custody stay there
+    CUSTODIAL.save(deps.storage, &custodial)?;
+    Ok(Response::new()
+        .add_attribute("action", "update_custodial")
+        .add_attribute("custodial", custodial.to_string())
+        .add_event(
+            event("update_custodial", &env).add_attribute("custodial", custodial.to_string()),
+        ))
+}
+
+fn load_controller(deps: Deps, token_id: &str) -> Result<Addr, ContractError> {
+    CONTROLLERS
+        .may_load(deps.storage, token_id)?
+        .ok_or_else(|| ContractError::NotInCustody {
+            token_id: token_id.to_string(),
+        })
+}
+
+pub fn execute_update_path_metadata(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    token_id: String,
+    mut metadata: whoami::msg::Metadata,
+) -> Result<Response, ContractError> {
+    if load_controller(deps.as_ref(), &token_id)? != info.sender {
+        return Err(ContractError::Unauthorized {});
+    }
+    let config = CONFIG.load(deps.storage)?;
+    let record = MINT_RECORDS.load(deps.storage, &token_id)?;
+
+    // Controllers can't move the path out from under its parent
+    metadata.parent_token_id = Some(record.parent);
+    let update_msg = WasmMsg::Execute {
+        contract_addr: config.whoami_address,
+        msg: to_binary(&whoami::msg::ExecuteMsg::UpdateMetadata(
+            whoami::msg::UpdateMetadataMsg {
+                token_id: token_id.clone(),
+                metadata,
+            },
+        ))?,
+        funds: vec![],
+    };
+
+    Ok(Response::new()
+        .add_attribute("action", "update_path_metadata")
+        .add_attribute("token_id", &token_id)
+        .add_event(
+            event("update_path_metadata", &env)
+                .add_attribute("token_id", token_id)
+                .add_attribute("controller", info.sender.as_str()),
+        )
+        .add_message(update_msg))
+}
+
+pub fn execute_update_controller(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    token_id: String,
+    controller: String,
+) -> Result<Response, ContractError> {
+    let config = CONFIG.load(deps.storage)?;
+    if info.sender != config.admin {
+        return Err(ContractError::Unauthorized {});
+    }
+    load_controller(deps.as_ref(), &token_id)?;
+
+    let controller = deps.api.addr_validate(&controller)?;
+    CONTROLLERS.save(deps.storage, &token_id, &controller)?;
+
+    Ok(Response::new()
+        .add_attribute("action", "update_controller")
+        .add_attribute("token_id", &token_id)
+        .add_event(
+            event("update_controller", &env)
+                .add_attribute("token_id", token_id)
+                .add_attribute("controller", controller.as_str()),
+        ))
+}
+
+pub fn execute_revoke_path(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    token_id: String,
+) -> Result<Response, ContractError> {
+    let config = CONFIG.load(deps.storage)?;
+    if info.sender != config.admin {
+        return Err(ContractError::Unauthorized {});
+    }
+    let controller = load_controller(deps.as_ref(), &token_id)?;
+
+    // Burning frees the path to be minted again
+    CONTROLLERS.remove(deps.storage, &token_id);
+    MINT_RECORDS.remove(deps.storage, &token_id);
+    let burn_msg = WasmMsg::Execute {
+        contract_addr: config.whoami_address,
+        msg: to_binary(&whoami::msg::ExecuteMsg::Burn {
+            token_id: token_id.clone(),
+        })?,
+        funds: vec![],
+    };
+    let mut response = Response::new()
+        .add_attribute("action", "revoke_path")
+        .add_attribute("token_id", &token_id)
+        .add_message(burn_msg);
+
+    // A revoked path hands back whatever was staked for it straight away
+    if let Some(stake) = STAKES.may_load(deps.storage, &token_id)? {
+        response = response.add_message(release_stake(deps, &token_id, &stake)?);
+    }
+
+    Ok(response.add_event(
+        event("revoke_path", &env)
+            .add_attribute("token_id", token_id)
+            .add_attribute("controller", controller.as_str()),
+    ))
+}
+
+pub fn execute_update_staking(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    staking: Option<Staking>,
+) -> Result<Response, ContractError> {
+    let config = CONFIG.load(deps.storage)?;
+    if info.sender != config.admin {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    // Existing stakes keep the token and amount they were made with
+    let mut update_event = event("update_staking", &env);
+    match staking {
+        Some(staking) => {
+            assert_valid_payment_details(
+                deps.as_ref(),
+                &PaymentDetails::Cw20 {
+                    token_address: staking.token_address.clone(),
+                    amount: staking.amount,
+                },
+            )?;
+            let token_address = deps.api.addr_validate(&staking.token_address)?;
+            update_event = update_event
+                .add_attribute("cw20_address", token_address.as_str())
+                .add_attribute("amount", staking.amount.to_string());
+            STAKING.save(
+                deps.storage,
+                &StakingConfig {
+                    token_address,
+                    amount: staking.amount,
+                    unbonding_period: staking.unbonding_period,
+                    reclaim: staking.reclaim,
+                },
+            )?;
+        }
+        None => STAKING.remove(deps.storage),
+    }
+
+    Ok(Response::new()
+        .add_attribute("action", "update_staking")
+        .add_event(update_event))
+}
+
+pub fn execute_stake_for_path(
+    mut deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    staker: String,
+    path: String,
+    amount: Uint128,
+) -> Result<Response, ContractError> {
+    let staking = STAKING
+        .may_load(deps.storage)?
+        .ok_or(ContractError::StakingDisabled {})?;
+    if info.sender != staking.token_address {
+        return Err(ContractError::UnrecognisedToken {});
+    }
+    if amount != staking.amount {
+        return Err(ContractError::IncorrectStake {
+            amount: staking.amount,
+        });
+    }
+    let config = CONFIG.load(deps.storage)?;
+    let token_id = config.token_id.ok_or(ContractError::NoRootToken {})?;
+    assert_can_mint(deps.as_ref(), &staker, None)?;
+
+    let staker_addr = deps.api.addr_validate(&staker)?;
+    let full_token_id = format!("{}::{}", token_id, path);
+    STAKES.save(
+        deps.storage,
+        &full_token_id,
+        &Stake {
+            staker: staker_addr.clone(),
+            token_address: staking.token_address.clone(),
+            amount,
+            unbonding_until: None,
+        },
+    )?;
+    STAKER_PATHS.save(deps.storage, (&staker_addr, &full_token_id), &Empty {})?;
+    let total_staked = TOTAL_STAKED
+        .may_load(deps.storage, staking.token_address.as_str())?
+        .unwrap_or_default();
+    TOTAL_STAKED.save(
+        deps.storage,
+        staking.token_address.as_str(),
+        &(total_staked + amount),
+    )?;
+
+    // The stake stands in for payment, so the path is minted at no price
+    let response = mint(
+        deps.branch(),
+        env.clone(),
+        config.whoami_address,
+        token_id,
+        vec![path],
+        staker.clone(),
+        Uint128::zero(),
+        None,
+        Some(Uint128::zero()),
+        None,
+        None,
+    )?;
+    let stake_event = event("stake", &env)
+        .add_attribute("token_id", &full_token_id)
+        .add_attribute("staker", staker)
+        .add_attribute("amount", amount.to_string())
+        .add_attribute("cw20_address", staking.token_address.as_str());
+    Ok(response
+        .add_attribute("staked", amount.to_string())
+        .add_event(stake_event))
+}
+
+fn load_stake(deps: Deps, token_id: &str) -> Result<Stake, ContractError> {
+    STAKES
+        .may_load(deps.storage, token_id)?
+        .ok_or_else(|| ContractError::NotStaked {
+            token_id: token_id.to_string(),
+        })
+}
+
+// Drops the stake from storage and pays it back to the staker
+fn release_stake(deps: DepsMut, token_id: &str, stake: &Stake) -> StdResult<CosmosMsg> {
+    STAKES.remove(deps.storage, token_id);
+    STAKER_PATHS.remove(deps.storage, (&stake.staker, token_id));
+    let total_staked = TOTAL_STAKED
+        .may_load(deps.storage, stake.token_address.as_str())?
+        .unwrap_or_default();
+    TOTAL_STAKED.save(
+        deps.storage,
+        stake.token_address.as_str(),
+        &total_staked.saturating_sub(stake.amount),
+    )?;
+    payment_msg(
+        &PaymentDetails::Cw20 {
+            token_address: stake.token_address.to_string(),
+            amount: stake.amount,
+        },
+        stake.staker.to_string(),
+        stake.amount,
+    )
+}
+
+// The path is already held by this contract, either sent back or kept in custody
+pub fn execute_unstake(
+    deps: DepsMut,
+    env: Env,
+    sender: String,
+    token_id: String,
+) -> Result<Response, ContractError> {
+    let mut stake = load_stake(deps.as_ref(), &token_id)?;
+    if stake.staker != sender {
+        return Err(ContractError::Unauthorized {});
+    }
+    if stake.unbonding_until.is_some() {
+        return Err(ContractError::AlreadyUnbonding { token_id });
+    }
+
+    // Staking may have been turned off since, which leaves nothing to wait for
+    let unbonding_period = STAKING
+        .may_load(deps.storage)?
+        .map(|staking| staking.unbonding_period)
+        .unwrap_or_default();
+    let unbonding_until = env.block.height + unbonding_period;
+    stake.unbonding_until = Some(unbonding_until);
+    STAKES.save(deps.storage, &token_id, &stake)?;
+
+    Ok(Response::new()
+        .add_attribute("action", "unstake")
+        .add_attribute("token_id", &token_id)
+        .add_event(
+            event("unstake", &env)
+                .add_attribute("token_id", token_id)
+                .add_attribute("staker", sender)
+                .add_attribute("unbonding_until", unbonding_until.to_string()),
+        ))
+}
+
+pub fn execute_claim_stake(
+    mut deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    token_id: String,
+) -> Result<Response, ContractError> {
+    let stake = load_stake(deps.as_ref(), &token_id)?;
+    if stake.staker != info.sender {
+        return Err(ContractError::Unauthorized {});
+    }
+    let unbonding_until = stake
+        .unbonding_until
+        .ok_or_else(|| ContractError::NotUnbonding {
+            token_id: token_id.clone(),
+        })?;
+    if env.block.height < unbonding_until {
+        return Err(ContractError::StillUnbonding {
+            height: unbonding_until,
+        });
+    }
+
+    let config = CONFIG.load(deps.storage)?;
+    let reclaim = STAKING
+        .may_load(deps.storage)?
+        .map(|staking| staking.reclaim)
+        .unwrap_or_default();
+    let release_msg = release_stake(deps.branch(), &token_id, &stake)?;
+    CONTROLLERS.remove(deps.storage, &token_id);
+
+    // Reclaimed paths go to the admin, otherwise they're burned and can be minted again
+    let path_msg = if reclaim {
+        whoami::msg::ExecuteMsg::TransferNft {
+            recipient: config.admin.to_string(),
+            token_id: token_id.clone(),
+        }
+    } else {
+        MINT_RECORDS.remove(deps.storage, &token_id);
+        whoami::msg::ExecuteMsg::Burn {
+            token_id: token_id.clone(),
+        }
+    };
+    let claim_event = event("claim_stake", &env)
+        .add_attribute("token_id", &token_id)
+        .add_attribute("recipient", stake.staker.as_str())
+        .add_attribute("amount", stake.amount.to_string())
+        .add_attribute("cw20_address", stake.token_address.as_str())
+        .add_attribute("reclaimed", reclaim.to_string());
+
+    Ok(Response::new()
+        .add_attribute("action", "claim_stake")
+        .add_attribute("token_id", token_id)
+        .add_event(claim_event)
+        .add_message(release_msg)
+        .add_message(WasmMsg::Execute {
+            contract_addr: config.whoami_address,
+            msg: to_binary(&path_msg)?,
+            funds: vec![],
+        }))
+}
+
+fn load_listing(deps: Deps, token_id: &str) -> Result<Listing, ContractError> {
+    LISTINGS
+        .may_load(deps.storage, token_id)?
+        .ok_or_else(|| ContractError::UnknownListing {
+            token_id: token_id.to_string(),
+        })
+}
+
+pub fn execute_buy_path(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    token_id: String,
+) -> Result<Response, ContractError> {
+    let listing = load_listing(deps.as_ref(), &token_id)?;
+    let paid_amount = match &listing.price {
+        PaymentDetails::Native { denom, .. } => must_pay(&info, denom)?,
+        // cw20 listings are bought by sending the token with a BuyPath message
+        PaymentDetails::Cw20 { .. } => return Err(ContractError::UnrecognisedToken {}),
+    };
+    buy_path(
+        deps,
+        env,
+        info.sender.to_string(),
+        token_id,
+        listing,
+        paid_amount,
+    )
+}
+
+// Payment has already been checked to be in the listing's asset
+fn buy_path(
+    deps: DepsMut,
+    env: Env,
+    buyer: String,
+    token_id: String,
+    listing: Listing,
+    paid_amount: Uint128,
+) -> Result<Response, ContractError> {
+    let price = price_of(&listing.price);
+    if paid_amount != price {
+        return Err(ContractError::IncorrectListingPayment { price });
+    }
+    let config = CONFIG.load(deps.storage)?;
+    LISTINGS.remove(deps.storage, &token_id);
+
+    let royalty = royalty_amount(deps.as_ref(), price)?;
+    let proceeds = price - royalty;
+    let mut msgs = vec![];
+    if !proceeds.is_zero() {
+        msgs.push(payment_msg(
+            &listing.price,
+            listing.seller.to_string(),
+            proceeds,
+        )?);
+    }
+    if !royalty.is_zero() {
+        msgs.push(payment_msg(
+            &listing.price,
+            config.admin.to_string(),
+            royalty,
+        )?);
+    }
+    msgs.push(CosmosMsg::Wasm(WasmMsg::Execute {
+        contract_addr: config.whoami_address,
+        msg: to_binary(&whoami::msg::ExecuteMsg::TransferNft {
+            recipient: buyer.clone(),
+            token_id: token_id.clone(),
+        })?,
+        funds: vec![],
+    }));
+    let buy_event = event("buy_path", &env)
+        .add_attribute("token_id", &token_id)
+        .add_attribute("payer", &buyer)
+        .add_attribute("recipient", listing.seller.as_str())
+        .add_attribute("royalty_amount", royalty.to_string())
+        .add_attribute("royalty_recipient", config.admin.as_str());
+
+    Ok(Response::new()
+        .add_attribute("action", "buy_path")
+        .add_attribute("token_id", token_id)
+        .add_event(payment_attributes(buy_event, &listing.price, price))
+        .add_messages(msgs))
+}
+
+// Rounds down, the seller keeps any remainder
+fn royalty_amount(deps: Deps, sale_price: Uint128) -> StdResult<Uint128> {
+    let royalty_bps = ROYALTY_BPS.may_load(deps.storage)?.unwrap_or_default();
+    Ok(sale_price.multiply_ratio(royalty_bps, MAX_ROYALTY_BPS))
+}
+
+pub fn execute_update_royalty(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    royalty_bps: Option<u16>,
+) -> Result<Response, ContractError> {
+    let config = CONFIG.load(deps.storage)?;
+    if info.sender != config.admin {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    match royalty_bps {
+        Some(royalty_bps) if royalty_bps > MAX_ROYALTY_BPS => {
+            return Err(ContractError::InvalidRoyalty {})
+        }
+        Some(royalty_bps) => ROYALTY_BPS.save(deps.storage, &royalty_bps)?,
+        None => ROYALTY_BPS.remove(deps.storage),
+    }
+
+    let royalty_bps = royalty_bps.unwrap_or_default().to_string();
+    Ok(Response::new()
+        .add_attribute("action", "update_royalty")
+        .add_attribute("royalty_bps", &royalty_bps)
+        .add_event(event("update_royalty", &env).add_attribute("royalty_bps", royalty_bps)))
+}
+
+pub fn execute_cancel_listing(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    token_id: String,
+) -> Result<Response, ContractError> {
+    let listing = load_listing(deps.as_ref(), &token_id)?;
+    if listing.seller != info.sender {
+        return Err(ContractError::Unauthorized {});
+    }
+    let config = CONFIG.load(deps.storage)?;
+    LISTINGS.remove(deps.storage, &token_id);
+
+    let transfer_msg = WasmMsg::Execute {
+        contract_addr: config.whoami_address,
+        msg: to_binary(&whoami::msg::ExecuteMsg::TransferNft {
+            recipient: listing.seller.to_string(),
+            token_id: token_id.clone(),
+        })?,
+        funds: vec![],
+    };
+
+    Ok(Response::new()
+        .add_attribute("action", "cancel_listing")
+        .add_attribute("token_id", &token_id)
+        .add_event(
+            event("cancel_listing", &env)
+                .add_attribute("token_id", token_id)
+                .add_attribute("recipient", listing.seller.as_str()),
+        )
+        .add_message(transfer_msg))
+}
+
+pub fn execute_update_admin(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    new_admin: String,
+) -> Result<Response, ContractError> {
+    let mut config = CONFIG.load(deps.storage)?;
+    if info.sender != config.admin {
+        // Only existing admin can set
+        return Err(ContractError::Unauthorized {});
+    }
+    let old_admin = config.admin.clone();
+    let validated_new_admin = deps.api.addr_validate(&new_admin)?;
+
+    config.admin = validated_new_admin.clone();
+
+    CONFIG.save(deps.storage, &config)?;
+
+    Ok(Response::new()
+        .add_attribute("action", "update_admin")
+        .add_attribute("old_admin", old_admin.to_string())
+        .add_attribute("new_admin", validated_new_admin.to_string())
+        .add_event(
+            event("update_admin", &env)
+                .add_attribute("old_admin", old_admin.as_str())
+                .add_attribute("new_admin", validated_new_admin.as_str()),
+        ))
+}
+
+pub fn execute_withdraw_root_token(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+) -> Result<Response, ContractError> {
+    let mut config = CONFIG.load(deps.storage)?;
+    let admin = config.admin.clone();
+    let whoami_address = config.whoami_address.clone();
+
+    if info.sender != admin {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    if config.token_id.is_none() {
+        return Err(ContractError::NoRootToken {});
+    }
+    let token_id = config.token_id.unwrap();
+
+    let transfer_msg = whoami::msg::ExecuteMsg::TransferNft {
+        recipient: admin.to_string(),
+        token_id: token_id.clone(),
+    };
+    let wasm_msg = WasmMsg::Execute {
+        contract_addr: whoami_address,
+        msg: to_binary(&transfer_msg)?,
+        funds: vec![],
+    };
+
+    config.token_id = None;
+    CONFIG.save(deps.storage, &config)?;
+
+    Ok(Response::new()
+        .add_attribute("action", "withdraw_root_token")
+        .add_event(
+            event("withdraw_root", &env)
+                .add_attribute("root", token_id)
+                .add_attribute("recipient", admin.as_str()),
+        )
+        .add_message(wasm_msg))
+}
+
+pub fn execute_withdraw_payments(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+) -> Result<Response, ContractError> {
+    let config = CONFIG.load(deps.storage)?;
+    let payment_details = PAYMENT_DETAILS.may_load(deps.storage)?;
+    if info.sender != config.admin {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    if payment_details.is_none() {
+        return Err(ContractError::NoPaymentsToCollect {});
+    }
+    let payment_details = payment_details.unwrap();
+
+    let amount = collected_balance(deps.as_ref(), &env, &payment_details)?;
+    if amount.is_zero() {
+        return Err(ContractError::NoPaymentsToCollect {});
+    }
+    let msg = payment_msg(&payment_details, config.admin.to_string(), amount)?;
+    let withdraw_event =
+        event("withdraw_payments", &env).add_attribute("recipient", config.admin.as_str());
+
+    Ok(Response::new()
+        .add_attribute("action", "withdraw_payments")
+        .add_event(payment_attributes(withdraw_event, &payment_details, amount))
+        .add_message(msg))
+}
+
+pub fn execute_update_gating_rules(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    rules: Vec<HolderRule>,
+) -> Result<Response, ContractError> {
+    let config = CONFIG.load(deps.storage)?;
+    if info.sender != config.admin {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    let validated_rules = rules
+        .into_iter()
+        .map(|rule| validate_holder_rule(deps.as_ref(), rule))
+        .collect::<Result<Vec<_>, _>>()?;
+
+    GATING_RULES.save(deps.storage, &validated_rules)?;
+
+    Ok(Response::new()
+        .add_attribute("action", "update_gating_rules")
+        .add_attribute("rules", validated_rules.len().to_string())
+        .add_event(
+            event("update_gating_rules", &env)
+                .add_attribute("rules", validated_rules.len().to_string()),
+        ))
+}
+
+pub fn execute_update_discounts(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    discounts: Vec<HolderDiscount>,
+) -> Result<Response, ContractError> {
+    let config = CONFIG.load(deps.storage)?;
+    if info.sender != config.admin {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    let mut validated_discounts = vec![];
+    for holder_discount in discounts {
+        if holder_discount.discount >= Decimal::one() {
+            return Err(ContractError::InvalidDiscount {});
+        }
+        validated_discounts.push(HolderDiscount {
+            priority: holder_discount.priority,
+            rule: validate_holder_rule(deps.as_ref(), holder_discount.rule)?,
+            discount: holder_discount.discount,
+        });
+    }
+    // Highest priority first so the first matching discount is the one applied
+    validated_discounts.sort_by_key(|holder_discount| Reverse(holder_discount.priority));
+
+    DISCOUNTS.save(deps.storage, &validated_discounts)?;
+
+    Ok(Response::new()
+        .add_attribute("action", "update_discounts")
+        .add_attribute("discounts", validated_discounts.len().to_string())
+        .add_event(
+            event("update_discounts", &env)
+                .add_attribute("discounts", validated_discounts.len().to_string()),
+        ))
+}
+
+pub fn execute_update_bundle_discounts(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    bundle_discounts: Vec<BundleDiscount>,
+) -> Result<Response, ContractError> {
+    let config = CONFIG.load(deps.storage)?;
+    if info.sender != config.admin {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    let mut validated_discounts = vec![];
+    for bundle_discount in bundle_discounts {
+        if bundle_discount.min_quantity < 2 {
+            return Err(ContractError::InvalidBundleQuantity {});
+        }
+        if bundle_discount.discount >= Decimal::one() {
+            return Err(ContractError::InvalidDiscount {});
+        }
+        validated_discounts.push(bundle_discount);
+    }
+    // Largest quantity first so a batch gets the biggest tier it reaches
+    validated_discounts.sort_by_key(|bundle_discount| Reverse(bundle_discount.min_quantity));
+
+    BUNDLE_DISCOUNTS.save(deps.storage, &validated_discounts)?;
+
+    Ok(Response::new()
+        .add_attribute("action", "update_bundle_discounts")
+        .add_attribute("bundle_discounts", validated_discounts.len().to_string())
+        .add_event(
+            event("update_bundle_discounts", &env)
+                .add_attribute("bundle_discounts", validated_discounts.len().to_string()),
+        ))
+}
+
+pub fn execute_update_referral_config(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    referral_config: Option<ReferralConfig>,
+) -> Result<Response, ContractError> {
     let config = CONFIG.load(deps.storage)?;
-    let payment_details = PAYMENT_DETAILS.may_load(deps.storage)?;
-    if config.token_id.is_none() {
-        // No token to mint off of
-        return Err(ContractError::NoRootToken {});
+    if info.sender != config.admin {
+        return Err(ContractError::Unauthorized {});
     }
-    let token_id = config.token_id.unwrap();
 
-    if let Some(payment_details) = payment_details {
-        match payment_details {
-            PaymentDetails::Native { denom, amount } => {
-                let paid_amount = must_pay(&info, &denom)?;
-                mint(
-                    env,
-                    config.whoami_address,
-                    token_id,
-                    path,
-                    info.sender.to_string(),
-                    paid_amount,
-                    amount,
-                )
-            }
-            // TODO: Improve error
-            PaymentDetails::Cw20 { .. } => Err(ContractError::Unauthorized {}),
+    let mut referral_event = event("update_referral_config", &env);
+    match referral_config {
+        Some(referral_config) => {
+            assert_valid_shares(deps.as_ref(), None, Some(referral_config.share), None)?;
+            REFERRAL_CONFIG.save(deps.storage, &referral_config)?;
+            referral_event = referral_event
+                .add_attribute("share", referral_config.share.to_string())
+                .add_attribute("payout", payout_name(&referral_config.payout));
+        }
+        None => {
+            REFERRAL_CONFIG.remove(deps.storage);
+            referral_event = referral_event.add_attribute("share", Decimal::zero().to_string());
         }
-    } else {
-        nonpayable(&info)?;
-        mint(
-            env,
-            config.whoami_address,
-            token_id,
-            path,
-            info.sender.to_string(),
-            Uint128::zero(),
-            Uint128::zero(),
-        )
     }
+
+    Ok(Response::new()
+        .add_attribute("action", "update_referral_config")
+        .add_event(referral_event))
 }
 
-pub fn execute_update_admin(
+pub fn execute_update_burn_share(
     deps: DepsMut,
-    _env: Env,
+    env: Env,
     info: MessageInfo,
-    new_admin: String,
+    burn_share: Option<Decimal>,
 ) -> Result<Response, ContractError> {
-    let mut config = CONFIG.load(deps.storage)?;
+    let config = CONFIG.load(deps.storage)?;
     if info.sender != config.admin {
-        // Only existing admin can set
         return Err(ContractError::Unauthorized {});
     }
-    let old_admin = config.admin.clone();
-    let validated_new_admin = deps.api.addr_validate(&new_admin)?;
 
-    config.admin = validated_new_admin.clone();
+    match burn_share {
+        Some(burn_share) => {
+            assert_valid_shares(deps.as_ref(), None, None, Some(burn_share))?;
+            BURN_SHARE.save(deps.storage, &burn_share)?;
+        }
+        None => BURN_SHARE.remove(deps.storage),
+    }
 
-    CONFIG.save(deps.storage, &config)?;
+    let burn_share = burn_share.unwrap_or_else(Decimal::zero).to_string();
+    Ok(Response::new()
+        .add_attribute("action", "update_burn_share")
+        .add_attribute("burn_share", &burn_share)
+        .add_event(event("update_burn_share", &env).add_attribute("burn_share", burn_share)))
+}
+
+pub fn execute_claim_referral_rewards(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+) -> Result<Response, ContractError> {
+    let payment_details = PAYMENT_DETAILS.may_load(deps.storage)?;
+    let mut stats = REFERRER_STATS
+        .may_load(deps.storage, &info.sender)?
+        .unwrap_or_default();
+    if payment_details.is_none() || stats.claimable.is_zero() {
+        return Err(ContractError::NoReferralRewards {});
+    }
+    let payment_details = payment_details.unwrap();
+
+    let amount = stats.claimable;
+    stats.claimable = Uint128::zero();
+    REFERRER_STATS.save(deps.storage, &info.sender, &stats)?;
+
+    let owed = REFERRAL_REWARDS_OWED
+        .may_load(deps.storage)?
+        .unwrap_or_default();
+    REFERRAL_REWARDS_OWED.save(deps.storage, &owed.saturating_sub(amount))?;
+    let claim_event =
+        event("claim_referral_rewards", &env).add_attribute("recipient", info.sender.as_str());
 
     Ok(Response::new()
-        .add_attribute("action", "update_admin")
-        .add_attribute("old_admin", old_admin.to_string())
-        .add_attribute("new_admin", validated_new_admin.to_string()))
+        .add_attribute("action", "claim_referral_rewards")
+        .add_attribute("amount", amount.to_string())
+        .add_event(payment_attributes(claim_event, &payment_details, amount))
+        .add_message(payment_msg(
+            &payment_details,
+            info.sender.to_string(),
+            amount,
+        )?))
 }
 
-pub fn execute_withdraw_root_token(
+pub fn execute_update_platform_fee(
     deps: DepsMut,
-    _env: Env,
+    env: Env,
     info: MessageInfo,
+    fee_collector: String,
+    rate: Decimal,
 ) -> Result<Response, ContractError> {
-    let mut config = CONFIG.load(deps.storage)?;
-    let admin = config.admin.clone();
-    let whoami_address = config.whoami_address.clone();
+    let platform_fee = PLATFORM_FEE.may_load(deps.storage)?;
+    // The platform fee can only be changed by its collector, never by the admin
+    match platform_fee {
+        Some(platform_fee) if platform_fee.fee_collector == info.sender => {}
+        _ => return Err(ContractError::Unauthorized {}),
+    }
 
-    if info.sender != admin {
+    let fee_collector = deps.api.addr_validate(&fee_collector)?;
+    assert_valid_shares(deps.as_ref(), Some(rate), None, None)?;
+    PLATFORM_FEE.save(
+        deps.storage,
+        &PlatformFeeConfig {
+            fee_collector: fee_collector.clone(),
+            rate,
+        },
+    )?;
+
+    Ok(Response::new()
+        .add_attribute("action", "update_platform_fee")
+        .add_attribute("fee_collector", fee_collector.to_string())
+        .add_attribute("rate", rate.to_string())
+        .add_event(
+            event("update_platform_fee", &env)
+                .add_attribute("fee_collector", fee_collector.as_str())
+                .add_attribute("rate", rate.to_string()),
+        ))
+}
+
+pub fn execute_update_reserved_paths(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    add: Vec<String>,
+    remove: Vec<String>,
+) -> Result<Response, ContractError> {
+    let config = CONFIG.load(deps.storage)?;
+    if info.sender != config.admin {
         return Err(ContractError::Unauthorized {});
     }
 
-    if config.token_id.is_none() {
-        return Err(ContractError::NoRootToken {});
+    for path in &add {
+        validate_path(path)?;
+        RESERVED_PATHS.save(deps.storage, path, &Empty {})?;
+    }
+    for path in &remove {
+        RESERVED_PATHS.remove(deps.storage, path);
     }
-    let token_id = config.token_id.unwrap();
 
-    let transfer_msg = whoami::msg::ExecuteMsg::TransferNft {
-        recipient: admin.to_string(),
-        token_id,
-    };
-    let wasm_msg = WasmMsg::Execute {
-        contract_addr: whoami_address,
-        msg: to_binary(&transfer_msg)?,
-        funds: vec![],
-    };
+    Ok(Response::new()
+        .add_attribute("action", "update_reserved_paths")
+        .add_attribute("added", add.len().to_string())
+        .add_attribute("removed", remove.len().to_string())
+        .add_event(
+            event("update_reserved_paths", &env)
+                .add_attribute("added", add.join(","))
+                .add_attribute("removed", remove.join(",")),
+        ))
+}
 
-    config.token_id = None;
-    CONFIG.save(deps.storage, &config)?;
+pub fn execute_add_hook(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    addr: String,
+) -> Result<Response, ContractError> {
+    let config = CONFIG.load(deps.storage)?;
+    if info.sender != config.admin {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    let hook = deps.api.addr_validate(&addr)?;
+    let mut hooks = HOOKS.may_load(deps.storage)?.unwrap_or_default();
+    if hooks.contains(&hook) {
+        return Err(ContractError::HookAlreadyRegistered {
+            hook: hook.to_string(),
+        });
+    }
+    hooks.push(hook.clone());
+    HOOKS.save(deps.storage, &hooks)?;
 
     Ok(Response::new()
-        .add_attribute("action", "withdraw_root_token")
-        .add_message(wasm_msg))
+        .add_attribute("action", "add_hook")
+        .add_attribute("hook", hook.to_string())
+        .add_event(event("add_hook", &env).add_attribute("hook", hook.as_str())))
 }
 
-pub fn execute_withdraw_payments(
+pub fn execute_remove_hook(
     deps: DepsMut,
     env: Env,
     info: MessageInfo,
+    addr: String,
 ) -> Result<Response, ContractError> {
     let config = CONFIG.load(deps.storage)?;
-    let payment_details = PAYMENT_DETAILS.may_load(deps.storage)?;
     if info.sender != config.admin {
         return Err(ContractError::Unauthorized {});
     }
 
-    if payment_details.is_none() {
-        return Err(ContractError::NoPaymentsToCollect {});
+    let hook = deps.api.addr_validate(&addr)?;
+    let mut hooks = HOOKS.may_load(deps.storage)?.unwrap_or_default();
+    if !hooks.contains(&hook) {
+        return Err(ContractError::HookNotRegistered {
+            hook: hook.to_string(),
+        });
     }
-    let payment_details = payment_details.unwrap();
+    hooks.retain(|registered| *registered != hook);
+    HOOKS.save(deps.storage, &hooks)?;
 
-    let payment_msg = match payment_details {
-        PaymentDetails::Cw20 {
-            token_address,
-            amount: _,
-        } => {
-            let resp: BalanceResponse = deps.querier.query_wasm_smart(
-                &token_address,
-                &Cw20QueryMsg::Balance {
-                    address: env.contract.address.to_string(),
-                },
-            )?;
-            if resp.balance.is_zero() {
-                return Err(ContractError::NoPaymentsToCollect {});
+    Ok(Response::new()
+        .add_attribute("action", "remove_hook")
+        .add_attribute("hook", hook.to_string())
+        .add_event(event("remove_hook", &env).add_attribute("hook", hook.as_str())))
+}
+
+// Mints reply always rather than only on error, so the pending path is removed after a
+// successful mint too instead of being left behind in storage
+#[cfg_attr(not(feature = "library"), entry_point)]
+pub fn reply(deps: DepsMut, _env: Env, msg: Reply) -> Result<Response, ContractError> {
+    match msg.id {
+        id if (MINT_PATH_REPLY_ID..MINT_PATH_REPLY_ID + MAX_BATCH_SIZE as u64).contains(&id) => {
+            let path = PENDING_MINTS.load(deps.storage, id)?;
+            PENDING_MINTS.remove(deps.storage, id);
+            match msg.result {
+                // Erroring here reverts the whole mint, handing back any payment already moved
+                SubMsgResult::Err(error) => Err(mint_path_error(path, error)),
+                SubMsgResult::Ok(_) => Ok(Response::new()),
             }
+        }
+        id => Err(ContractError::UnknownReplyId { id }),
+    }
+}
 
-            let send_msg = Cw20ExecuteMsg::Transfer {
-                recipient: config.admin.to_string(),
-                amount: resp.balance,
-            };
+fn parse_version(version: &str) -> Result<(u64, u64, u64), ContractError> {
+    let parts = version
+        .split('.')
+        .map(|part| part.parse::<u64>())
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|_err| ContractError::InvalidVersion {
+            version: version.to_string(),
+        })?;
+    match parts.as_slice() {
+        [major, minor, patch] => Ok((*major, *minor, *patch)),
+        _ => Err(ContractError::InvalidVersion {
+            version: version.to_string(),
+        }),
+    }
+}
 
-            CosmosMsg::Wasm(WasmMsg::Execute {
-                contract_addr: token_address,
-                msg: to_binary(&send_msg)?,
-                funds: vec![],
-            })
-        }
-        PaymentDetails::Native { denom, amount: _ } => {
-            let balance = deps.querier.query_balance(env.contract.address, denom)?;
-            if balance.amount.is_zero() {
-                return Err(ContractError::NoPaymentsToCollect {});
-            }
+// 1.0.0 stored the same Config and PaymentDetails shapes, but its addresses were saved as given
+// and its payment details predate the checks later handlers rely on, so both are re-checked and
+// saved in their validated form. Settings added since 1.0.0 are left unset and read as defaults
+fn migrate_config_v1_0(deps: DepsMut) -> Result<(), ContractError> {
+    let mut config = CONFIG.load(deps.storage)?;
+    config.whoami_address = deps.api.addr_validate(&config.whoami_address)?.to_string();
+    config.admin = deps.api.addr_validate(config.admin.as_str())?;
+    CONFIG.save(deps.storage, &config)?;
 
-            CosmosMsg::Bank(BankMsg::Send {
-                to_address: config.admin.to_string(),
-                amount: vec![balance],
-            })
-        }
-    };
+    if let Some(payment_details) = PAYMENT_DETAILS.may_load(deps.storage)? {
+        assert_valid_payment_details(deps.as_ref(), &payment_details)?;
+        PAYMENT_DETAILS.save(deps.storage, &payment_details)?;
+    }
+    Ok(())
+}
+
+#[cfg_attr(not(feature = "library"), entry_point)]
+pub fn migrate(mut deps: DepsMut, env: Env, _msg: MigrateMsg) -> Result<Response, ContractError> {
+    let stored = get_contract_version(deps.storage)?;
+    if stored.contract != CONTRACT_NAME {
+        return Err(ContractError::CannotMigrate {
+            previous_contract: stored.contract,
+        });
+    }
+
+    let stored_version = parse_version(&stored.version)?;
+    if stored_version > parse_version(CONTRACT_VERSION)? {
+        return Err(ContractError::CannotDowngrade {
+            from: stored.version,
+            to: CONTRACT_VERSION.to_string(),
+        });
+    }
+
+    // Each older release has its state brought up to this one before the version is bumped
+    if let (1, 0, _) = stored_version {
+        migrate_config_v1_0(deps.branch())?;
+    }
+    set_contract_version(deps.storage, CONTRACT_NAME, CONTRACT_VERSION)?;
 
     Ok(Response::new()
-        .add_attribute("action", "withdraw_payments")
-        .add_message(payment_msg))
+        .add_attribute("action", "migrate")
+        .add_attribute("from_version", &stored.version)
+        .add_attribute("to_version", CONTRACT_VERSION)
+        .add_event(
+            event("migrate", &env)
+                .add_attribute("from_version", stored.version)
+                .add_attribute("to_version", CONTRACT_VERSION),
+        ))
 }
 
 #[cfg_attr(not(feature = "library"), entry_point)]
@@ -420,37 +3294,325 @@ pub fn query(deps: Deps, env: Env, msg: QueryMsg) -> StdResult<Binary> {
             payment_details: PAYMENT_DETAILS.may_load(deps.storage)?,
         }),
         QueryMsg::PaymentDetailsBalance {} => query_payment_details_balance(deps, env),
+        QueryMsg::GatingRules {} => to_binary(&GatingRulesResponse {
+            rules: GATING_RULES.may_load(deps.storage)?.unwrap_or_default(),
+        }),
+        QueryMsg::CanMint { address } => query_can_mint(deps, address),
+        QueryMsg::Discounts {} => to_binary(&DiscountsResponse {
+            discounts: DISCOUNTS.may_load(deps.storage)?.unwrap_or_default(),
+        }),
+        QueryMsg::ReferralConfig {} => to_binary(&ReferralConfigResponse {
+            referral_config: REFERRAL_CONFIG.may_load(deps.storage)?,
+        }),
+        QueryMsg::ReferrerStats { address } => query_referrer_stats(deps, address),
+        QueryMsg::PathAvailable { path } => query_path_available(deps, path),
+        QueryMsg::Info {} => query_info(deps, env),
+        QueryMsg::Listing { token_id } => {
+            let listing = LISTINGS.load(deps.storage, &token_id)?;
+            to_binary(&ListingResponse {
+                token_id,
+                seller: listing.seller.to_string(),
+                price: listing.price,
+            })
+        }
+        QueryMsg::Listings { start_after, limit } => query_listings(deps, start_after, limit),
+        QueryMsg::Staking {} => to_binary(&StakingResponse {
+            staking: STAKING.may_load(deps.storage)?,
+        }),
+        QueryMsg::TotalStaked {} => {
+            let token_address = STAKING
+                .may_load(deps.storage)?
+                .map(|staking| staking.token_address.to_string());
+            let total_staked = match &token_address {
+                Some(token_address) => TOTAL_STAKED
+                    .may_load(deps.storage, token_address)?
+                    .unwrap_or_default(),
+                None => Uint128::zero(),
+            };
+            to_binary(&TotalStakedResponse {
+                token_address,
+                total_staked,
+            })
+        }
+        QueryMsg::Stakes {
+            address,
+            start_after,
+            limit,
+        } => query_stakes(deps, address, start_after, limit),
+        QueryMsg::Custodial {} => to_binary(&CustodialResponse {
+            custodial: CUSTODIAL.may_load(deps.storage)?.unwrap_or_default(),
+        }),
+        QueryMsg::Controller { token_id } => to_binary(&ControllerResponse {
+            controller: CONTROLLERS.load(deps.storage, &token_id)?.to_string(),
+            token_id,
+        }),
+        QueryMsg::ReturnPolicy {} => to_binary(&ReturnPolicyResponse {
+            return_policy: RETURN_POLICY.may_load(deps.storage)?,
+        }),
+        QueryMsg::Royalty {} => to_binary(&RoyaltyResponse {
+            royalty_bps: ROYALTY_BPS.may_load(deps.storage)?.unwrap_or_default(),
+            recipient: CONFIG.load(deps.storage)?.admin.to_string(),
+        }),
+        QueryMsg::RoyaltyInfo {
+            token_id,
+            sale_price,
+        } => {
+            // Only paths minted here carry the royalty, anything else is owed nothing
+            let royalty_amount = match MINT_RECORDS.may_load(deps.storage, &token_id)? {
+                Some(_) => royalty_amount(deps, sale_price)?,
+                None => Uint128::zero(),
+            };
+            to_binary(&RoyaltyInfoResponse {
+                address: CONFIG.load(deps.storage)?.admin.to_string(),
+                royalty_amount,
+            })
+        }
+        QueryMsg::UsdPricing {} => to_binary(&UsdPricingResponse {
+            usd_pricing: USD_PRICING.may_load(deps.storage)?,
+        }),
+        QueryMsg::AllowlistRoot {} => to_binary(&AllowlistRootResponse {
+            root: ALLOWLIST_ROOT.may_load(deps.storage)?,
+        }),
+        QueryMsg::AllowlistUsage { address, quota } => {
+            let address = deps.api.addr_validate(&address)?;
+            let used = ALLOWLIST_USAGE
+                .may_load(deps.storage, &merkle::leaf_hash(address.as_str(), quota))?
+                .unwrap_or_default();
+            to_binary(&AllowlistUsageResponse {
+                used,
+                remaining: quota.saturating_sub(used),
+            })
+        }
+        QueryMsg::VoucherSigner {} => to_binary(&VoucherSignerResponse {
+            pubkey: VOUCHER_SIGNER.may_load(deps.storage)?,
+        }),
+        QueryMsg::CommitWindow {} => to_binary(&CommitWindowResponse {
+            commit_window: COMMIT_WINDOW
+                .may_load(deps.storage)?
+                .unwrap_or(DEFAULT_COMMIT_WINDOW),
+            require_commitment: REQUIRE_COMMITMENT
+                .may_load(deps.storage)?
+                .unwrap_or_default(),
+        }),
+        QueryMsg::SubRoot { token_id } => query_sub_root(deps, token_id),
+        QueryMsg::PathPrices { start_after, limit } => query_path_prices(deps, start_after, limit),
+        QueryMsg::Hooks {} => to_binary(&HooksResponse {
+            hooks: HOOKS
+                .may_load(deps.storage)?
+                .unwrap_or_default()
+                .into_iter()
+                .map(|hook| hook.to_string())
+                .collect(),
+        }),
+        QueryMsg::PlatformFee {} => to_binary(&PlatformFeeResponse {
+            platform_fee: PLATFORM_FEE.may_load(deps.storage)?,
+        }),
+        QueryMsg::Burned {} => to_binary(&BurnedResponse {
+            burn_share: BURN_SHARE.may_load(deps.storage)?,
+            total_burned: TOTAL_BURNED.may_load(deps.storage)?.unwrap_or_default(),
+        }),
+        QueryMsg::QuotePrice { address, path } => {
+            let address = deps.api.addr_validate(&address)?;
+            to_binary(&quote_price(deps, &env, address.as_str(), &path, None)?)
+        }
+        QueryMsg::BundleDiscounts {} => to_binary(&BundleDiscountsResponse {
+            bundle_discounts: BUNDLE_DISCOUNTS.may_load(deps.storage)?.unwrap_or_default(),
+        }),
+        QueryMsg::QuotePaths { address, paths } => {
+            let address = deps.api.addr_validate(&address)?;
+            to_binary(&quote_paths(deps, &env, address.as_str(), &paths, None)?)
+        }
     }
 }
 
+pub fn query_can_mint(deps: Deps, address: String) -> StdResult<Binary> {
+    let address = deps.api.addr_validate(&address)?;
+    let rules = GATING_RULES.may_load(deps.storage)?.unwrap_or_default();
+    let failed_rules = gating_failures(deps, rules, address.as_str())?;
+    to_binary(&CanMintResponse {
+        can_mint: failed_rules.is_empty(),
+        failed_rules,
+    })
+}
+
 pub fn query_payment_details_balance(deps: Deps, env: Env) -> StdResult<Binary> {
     let payment_details = PAYMENT_DETAILS.may_load(deps.storage)?;
-    if let Some(payment_details) = payment_details {
-        match payment_details.clone() {
-            PaymentDetails::Cw20 { token_address, .. } => {
-                let resp: BalanceResponse = deps.querier.query_wasm_smart(
-                    &token_address,
-                    &Cw20QueryMsg::Balance {
-                        address: env.contract.address.to_string(),
-                    },
-                )?;
-                to_binary(&PaymentDetailsBalanceResponse {
-                    payment_details: Some(payment_details),
-                    amount: resp.balance,
-                })
-            }
-            PaymentDetails::Native { denom, .. } => {
-                let balance = deps.querier.query_balance(env.contract.address, denom)?;
-                to_binary(&PaymentDetailsBalanceResponse {
-                    payment_details: Some(payment_details),
-                    amount: balance.amount,
-                })
-            }
-        }
-    } else {
-        to_binary(&PaymentDetailsBalanceResponse {
-            payment_details: None,
-            amount: Uint128::zero(),
+    let amount = match &payment_details {
+        Some(payment_details) => collected_balance(deps, &env, payment_details)?,
+        None => Uint128::zero(),
+    };
+    to_binary(&PaymentDetailsBalanceResponse {
+        payment_details,
+        amount,
+    })
+}
+
+pub fn query_info(deps: Deps, env: Env) -> StdResult<Binary> {
+    let config = CONFIG.load(deps.storage)?;
+    let payment_details = PAYMENT_DETAILS.may_load(deps.storage)?;
+    let collected_balance = match &payment_details {
+        Some(payment_details) => collected_balance(deps, &env, payment_details)?,
+        None => Uint128::zero(),
+    };
+    // The base price as it is charged right now, which moves with the oracle under USD pricing
+    let current_price = match (&payment_details, USD_PRICING.may_load(deps.storage)?) {
+        (Some(payment_details), Some(usd_pricing)) => Some(usd_payment_details(
+            deps,
+            &env,
+            payment_details.clone(),
+            &usd_pricing,
+        )?),
+        (payment_details, _) => payment_details.clone(),
+    };
+    to_binary(&InfoResponse {
+        token_id: config.token_id,
+        whoami_address: config.whoami_address,
+        admin: config.admin.to_string(),
+        payment_details,
+        current_price,
+        collected_balance,
+        minted_count: MINTED_COUNT.may_load(deps.storage)?.unwrap_or_default(),
+        contract_version: get_contract_version(deps.storage)?,
+    })
+}
+
+pub fn query_path_prices(
+    deps: Deps,
+    start_after: Option<String>,
+    limit: Option<u32>,
+) -> StdResult<Binary> {
+    let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+    let start = start_after.as_deref().map(Bound::exclusive);
+    let prices = PATH_PRICES
+        .range(deps.storage, start, None, Order::Ascending)
+        .take(limit)
+        .map(|item| {
+            item.map(|(path, payment_details)| PathPrice {
+                path,
+                payment_details,
+            })
+        })
+        .collect::<StdResult<Vec<_>>>()?;
+    to_binary(&PathPricesResponse {
+        prices,
+        patterns: PATTERN_PRICES.may_load(deps.storage)?.unwrap_or_default(),
+    })
+}
+
+pub fn query_stakes(
+    deps: Deps,
+    address: String,
+    start_after: Option<String>,
+    limit: Option<u32>,
+) -> StdResult<Binary> {
+    let address = deps.api.addr_validate(&address)?;
+    let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+    let start = start_after.as_deref().map(Bound::exclusive);
+    let stakes = STAKER_PATHS
+        .prefix(&address)
+        .keys(deps.storage, start, None, Order::Ascending)
+        .take(limit)
+        .map(|token_id| {
+            let token_id = token_id?;
+            let stake = STAKES.load(deps.storage, &token_id)?;
+            Ok(StakeResponse {
+                token_id,
+                token_address: stake.token_address.to_string(),
+                amount: stake.amount,
+                unbonding_until: stake.unbonding_until,
+            })
+        })
+        .collect::<StdResult<Vec<_>>>()?;
+    to_binary(&StakesResponse { stakes })
+}
+
+pub fn query_listings(
+    deps: Deps,
+    start_after: Option<String>,
+    limit: Option<u32>,
+) -> StdResult<Binary> {
+    let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+    let start = start_after.as_deref().map(Bound::exclusive);
+    let listings = LISTINGS
+        .range(deps.storage, start, None, Order::Ascending)
+        .take(limit)
+        .map(|item| {
+            item.map(|(token_id, listing)| ListingResponse {
+                token_id,
+                seller: listing.seller.to_string(),
+                price: listing.price,
+            })
         })
+        .collect::<StdResult<Vec<_>>>()?;
+    to_binary(&ListingsResponse { listings })
+}
+
+pub fn query_sub_root(deps: Deps, token_id: String) -> StdResult<Binary> {
+    let sub_root = SUB_ROOTS.load(deps.storage, &token_id)?;
+    to_binary(&SubRootResponse {
+        token_id,
+        owner: sub_root.owner.to_string(),
+        payment_details: sub_root.payment_details,
+        gating_rules: sub_root.gating_rules,
+    })
+}
+
+// Errors from another contract arrive as text, cw721 reports a missing token as "... not found"
+fn is_not_found(err: &StdError) -> bool {
+    match err {
+        StdError::NotFound { .. } => true,
+        err => err.to_string().ends_with("not found"),
+    }
+}
+
+pub fn query_path_available(deps: Deps, path: String) -> StdResult<Binary> {
+    let config = CONFIG.load(deps.storage)?;
+    if config.token_id.is_none() {
+        return to_binary(&PathAvailableResponse {
+            token_id: None,
+            status: PathStatus::NoRoot,
+        });
     }
+    let token_id = format!("{}::{}", config.token_id.unwrap(), path);
+
+    let status = if let Err(err) = validate_path(&path) {
+        PathStatus::Invalid {
+            reason: err.to_string(),
+        }
+    } else {
+        let owner: StdResult<OwnerOfResponse> = deps.querier.query_wasm_smart(
+            config.whoami_address,
+            &Cw721QueryMsg::OwnerOf {
+                token_id: token_id.clone(),
+                include_expired: None,
+            },
+        );
+        // Only a missing token means the path is free, any other failure is passed on
+        match owner {
+            Ok(owner) => PathStatus::Taken { owner: owner.owner },
+            Err(err) if !is_not_found(&err) => return Err(err),
+            Err(_) if RESERVED_PATHS.may_load(deps.storage, &path)?.is_some() => {
+                PathStatus::Reserved
+            }
+            Err(_) => PathStatus::Available,
+        }
+    };
+
+    to_binary(&PathAvailableResponse {
+        token_id: Some(token_id),
+        status,
+    })
+}
+
+pub fn query_referrer_stats(deps: Deps, address: String) -> StdResult<Binary> {
+    let address = deps.api.addr_validate(&address)?;
+    let stats = REFERRER_STATS
+        .may_load(deps.storage, &address)?
+        .unwrap_or_default();
+    to_binary(&ReferrerStatsResponse {
+        address: address.to_string(),
+        referrals: stats.referrals,
+        total_earned: stats.total_earned,
+        claimable: stats.claimable,
+    })
 }
@@ -1,4 +1,4 @@
-use cosmwasm_std::StdError;
+use cosmwasm_std::{StdError, Uint128};
 use cw_utils::PaymentError;
 use thiserror::Error;
 
@@ -36,4 +36,190 @@ pub enum ContractError {
 
     #[error("The token address provided is not a valid CW20 token")]
     InvalidCw20 {},
+
+    #[error("Address does not meet any of the requirements to mint a path")]
+    MintingGated {},
+
+    #[error("Discounts must be less than 100%")]
+    InvalidDiscount {},
+
+    #[error("Payment shares cannot add up to more than 100%")]
+    InvalidShare {},
+
+    #[error("You cannot refer yourself")]
+    SelfReferral {},
+
+    #[error("No referral rewards are available to claim")]
+    NoReferralRewards {},
+
+    #[error("Path {path} is already taken")]
+    PathTaken { path: String },
+
+    #[error("Path {path} is not a valid name")]
+    InvalidPath { path: String },
+
+    #[error("Path {path} is reserved")]
+    PathReserved { path: String },
+
+    #[error("Failed to mint path {path}: {reason}")]
+    MintFailed { path: String, reason: String },
+
+    #[error("Unknown reply id: {id}")]
+    UnknownReplyId { id: u64 },
+
+    #[error("Cannot migrate from a different contract: {previous_contract}")]
+    CannotMigrate { previous_contract: String },
+
+    #[error("Cannot migrate from version {from} to older version {to}")]
+    CannotDowngrade { from: String, to: String },
+
+    #[error("Invalid contract version: {version}")]
+    InvalidVersion { version: String },
+
+    #[error("Path prices must be paid in the same token as the base price")]
+    InvalidPathPrice {},
+
+    #[error("{token_id} is not a registered sub-root")]
+    UnknownSubRoot { token_id: String },
+
+    #[error("{token_id} was not minted through this contract")]
+    InvalidSubRoot { token_id: String },
+
+    #[error("Hook {hook} is already registered")]
+    HookAlreadyRegistered { hook: String },
+
+    #[error("Hook {hook} is not registered")]
+    HookNotRegistered { hook: String },
+
+    #[error("No paths were given to mint")]
+    EmptyBatch {},
+
+    #[error("Cannot mint more than {max} paths at once")]
+    BatchTooLarge { max: u32 },
+
+    #[error("Path {path} appears more than once")]
+    DuplicatePath { path: String },
+
+    #[error("Bundle discounts must be for at least 2 paths")]
+    InvalidBundleQuantity {},
+
+    #[error("Price {price} is above the maximum price of {max_price}")]
+    PriceAboveMax { price: Uint128, max_price: Uint128 },
+
+    #[error("Commitments must be a 32 byte sha256 hash")]
+    InvalidCommitment {},
+
+    #[error("No commitment found for this path, sender and salt")]
+    CommitmentNotFound {},
+
+    #[error("Commitment cannot be revealed until block {height}")]
+    RevealTooEarly { height: u64 },
+
+    #[error("Commitment expired at block {height}")]
+    CommitmentExpired { height: u64 },
+
+    #[error("The reveal delay must be shorter than the expiry")]
+    InvalidCommitWindow {},
+
+    #[error("Paths can only be minted by committing to them first and revealing them")]
+    CommitmentRequired {},
+
+    #[error("Vouchers are not enabled")]
+    NoVoucherSigner {},
+
+    #[error("Voucher signer must be a compressed or uncompressed secp256k1 public key")]
+    InvalidVoucherSigner {},
+
+    #[error("Voucher signature is not valid")]
+    InvalidVoucherSignature {},
+
+    #[error("Voucher was issued to a different address")]
+    VoucherNotForSender {},
+
+    #[error("Voucher was issued for a different contract or chain")]
+    VoucherNotForContract {},
+
+    #[error("Voucher is not valid for path {path}")]
+    VoucherPathMismatch { path: String },
+
+    #[error("Voucher has expired")]
+    VoucherExpired {},
+
+    #[error("Voucher {nonce} has already been redeemed")]
+    VoucherUsed { nonce: u64 },
+
+    #[error("Allowlist root must be a 32 byte sha256 hash")]
+    InvalidAllowlistRoot {},
+
+    #[error("Address is not on the allowlist")]
+    NotOnAllowlist {},
+
+    #[error("Allowlist quota of {quota} paths has been used up")]
+    AllowlistQuotaExceeded { quota: u32 },
+
+    #[error("Allowance of {allowance} is less than the {required} needed to mint")]
+    InsufficientAllowance {
+        allowance: Uint128,
+        required: Uint128,
+    },
+
+    #[error("USD price must be more than zero")]
+    InvalidUsdPrice {},
+
+    #[error("Tolerance must be less than 100%")]
+    InvalidTolerance {},
+
+    #[error("{token_id} was not minted through this contract")]
+    UnknownPath { token_id: String },
+
+    #[error("{token_id} is not listed for sale")]
+    UnknownListing { token_id: String },
+
+    #[error("This listing costs exactly {price}")]
+    IncorrectListingPayment { price: Uint128 },
+
+    #[error("Royalty can be at most 10000 basis points")]
+    InvalidRoyalty {},
+
+    #[error("{token_id} is not held in custody")]
+    NotInCustody { token_id: String },
+
+    #[error("Staking for paths is not enabled")]
+    StakingDisabled {},
+
+    #[error("Staking for a path takes exactly {amount}")]
+    IncorrectStake { amount: Uint128 },
+
+    #[error("{token_id} is not backed by a stake")]
+    NotStaked { token_id: String },
+
+    #[error("{token_id} is backed by a stake, unstake it instead")]
+    PathStaked { token_id: String },
+
+    #[error("{token_id} is already unbonding")]
+    AlreadyUnbonding { token_id: String },
+
+    #[error("{token_id} has not been unstaked")]
+    NotUnbonding { token_id: String },
+
+    #[error("Stake is unbonding until height {height}")]
+    StillUnbonding { height: u64 },
+
+    #[error("Paths can not be returned")]
+    ReturnsDisabled {},
+
+    #[error("The return window closed at height {height}")]
+    ReturnWindowClosed { height: u64 },
+
+    #[error("The contract no longer holds the {refund} refund")]
+    RefundUnavailable { refund: Uint128 },
+
+    #[error("{token_id} has nothing to refund so it can't be returned")]
+    NothingToRefund { token_id: String },
+
+    #[error("Refund rate must be at most 100%")]
+    InvalidRefundRate {},
+
+    #[error("Paths can only be listed once a payment asset is configured")]
+    NoPaymentAsset {},
 }
@@ -1,11 +1,13 @@
 #[cfg(test)]
 mod tests {
     use crate::msg::{
-        ExecuteMsg, InstantiateMsg, PaymentDetails, PaymentDetailsBalanceResponse, QueryMsg,
-        ReceiveMsg,
+        BurnedResponse, CanMintResponse, ExecuteMsg, HolderDiscount, HolderRule, InfoResponse,
+        InstantiateMsg, PathAvailableResponse, PaymentDetails, PaymentDetailsBalanceResponse,
+        PlatformFee, PlatformFeeResponse, QueryMsg, QuotePriceResponse, ReceiveMsg, ReferralConfig,
+        ReferrerStatsResponse,
     };
     use crate::state::Config;
-    use cosmwasm_std::{coins, to_binary, Addr, Coin, Empty, Uint128};
+    use cosmwasm_std::{coins, to_binary, Addr, Coin, Decimal, Empty, Uint128};
     use cw20::{BalanceResponse, Cw20Coin, Cw20ExecuteMsg, Cw20QueryMsg};
     use cw721::{Cw721QueryMsg, OwnerOfResponse};
     use cw_multi_test::{
@@ -14,6 +16,8 @@ mod tests {
 
     const USER: &str = "addr1";
     const ADMIN: &str = "addr2";
+    const OUTSIDER: &str = "addr3";
+    const FEE_COLLECTOR: &str = "addr4";
     const NATIVE_DENOM: &str = "ujunox";
     const INVALID_DENOM: &str = "uinvalid";
 
@@ -22,7 +26,9 @@ mod tests {
             crate::contract::execute,
             crate::contract::instantiate,
             crate::contract::query,
-        );
+        )
+        .with_reply(crate::contract::reply)
+        .with_migrate(crate::contract::migrate);
         Box::new(contract)
     }
 
@@ -102,8 +108,15 @@ mod tests {
             mint: None,
             marketing: None,
         };
-        app.instantiate_contract(cw20_id, Addr::unchecked(ADMIN), &msg, &[], "cw20", None)
-            .unwrap()
+        app.instantiate_contract(
+            cw20_id,
+            Addr::unchecked(ADMIN),
+            &msg,
+            &[],
+            "cw20",
+            Some(ADMIN.to_string()),
+        )
+        .unwrap()
     }
 
     fn instantiate_whoami(app: &mut App) -> Addr {
@@ -129,12 +142,14 @@ mod tests {
         app: &mut App,
         whoami_addr: Addr,
         payment_details: Option<PaymentDetails>,
+        platform_fee: Option<PlatformFee>,
     ) -> Addr {
         let whoami_paths = app.store_code(contract_whoami_paths());
         let msg = InstantiateMsg {
             admin: ADMIN.to_string(),
             whoami_address: whoami_addr.to_string(),
             payment_details,
+            platform_fee,
         };
         app.instantiate_contract(
             whoami_paths,
@@ -142,14 +157,23 @@ mod tests {
             &msg,
             &[],
             "whoami-paths",
-            None,
+            Some(ADMIN.to_string()),
         )
         .unwrap()
     }
 
     fn setup_test_case(app: &mut App, payment_details: Option<PaymentDetails>) -> (Addr, Addr) {
+        setup_test_case_with_platform_fee(app, payment_details, None)
+    }
+
+    fn setup_test_case_with_platform_fee(
+        app: &mut App,
+        payment_details: Option<PaymentDetails>,
+        platform_fee: Option<PlatformFee>,
+    ) -> (Addr, Addr) {
         let whoami_addr = instantiate_whoami(app);
-        let paths_addr = instantiate_whoami_paths(app, whoami_addr.clone(), payment_details);
+        let paths_addr =
+            instantiate_whoami_paths(app, whoami_addr.clone(), payment_details, platform_fee);
         app.update_block(next_block);
         (whoami_addr, paths_addr)
     }
@@ -159,21 +183,18 @@ mod tests {
         payment_details: Option<PaymentDetails>,
     ) -> (Addr, Addr, String) {
         let (whoami, paths) = setup_test_case(app, payment_details);
+        let token_id = transfer_root_name(app, whoami.clone(), paths.clone());
+        (whoami, paths, token_id)
+    }
 
+    fn transfer_root_name(app: &mut App, whoami: Addr, paths: Addr) -> String {
         // Mint the name
         let token_id = "root_name".to_string();
         mint_name(app, whoami.clone(), ADMIN, &token_id).unwrap();
 
         // Transfer to the contract
-        transfer_name(
-            app,
-            whoami.clone(),
-            ADMIN,
-            paths.to_string(),
-            token_id.clone(),
-        )
-        .unwrap();
-        (whoami, paths, token_id)
+        transfer_name(app, whoami, ADMIN, paths.to_string(), token_id.clone()).unwrap();
+        token_id
     }
 
     fn mint_name(
@@ -232,12 +253,27 @@ mod tests {
         sender: &str,
         path: &str,
         payment: Vec<Coin>,
+    ) -> anyhow::Result<AppResponse> {
+        mint_path_native_referred(app, paths_addr, sender, path, None, payment)
+    }
+
+    fn mint_path_native_referred(
+        app: &mut App,
+        paths_addr: Addr,
+        sender: &str,
+        path: &str,
+        referrer: Option<&str>,
+        payment: Vec<Coin>,
     ) -> anyhow::Result<AppResponse> {
         app.execute_contract(
             Addr::unchecked(sender),
             paths_addr,
             &ExecuteMsg::MintPath {
                 path: path.to_string(),
+                referrer: referrer.map(|referrer| referrer.to_string()),
+                parent: None,
+                max_price: None,
+                allowlist: None,
             },
             &payment,
         )
@@ -250,12 +286,28 @@ mod tests {
         sender: &str,
         amount: Uint128,
         path: &str,
+    ) -> anyhow::Result<AppResponse> {
+        mint_path_cw20_referred(app, cw20_addr, paths_addr, sender, amount, path, None)
+    }
+
+    fn mint_path_cw20_referred(
+        app: &mut App,
+        cw20_addr: Addr,
+        paths_addr: Addr,
+        sender: &str,
+        amount: Uint128,
+        path: &str,
+        referrer: Option<&str>,
     ) -> anyhow::Result<AppResponse> {
         let msg = Cw20ExecuteMsg::Send {
             contract: paths_addr.to_string(),
             amount,
             msg: to_binary(&ReceiveMsg::MintPath {
                 path: path.to_string(),
+                referrer: referrer.map(|referrer| referrer.to_string()),
+                parent: None,
+                max_price: None,
+                allowlist: None,
             })?,
         };
         app.execute_contract(Addr::unchecked(sender), cw20_addr, &msg, &[])
@@ -289,6 +341,91 @@ mod tests {
         app.execute_contract(Addr::unchecked(sender), paths_addr, &msg, &[])
     }
 
+    fn update_gating_rules(
+        app: &mut App,
+        paths_addr: Addr,
+        sender: &str,
+        rules: Vec<HolderRule>,
+    ) -> anyhow::Result<AppResponse> {
+        let msg = ExecuteMsg::UpdateGatingRules { rules };
+        app.execute_contract(Addr::unchecked(sender), paths_addr, &msg, &[])
+    }
+
+    fn update_discounts(
+        app: &mut App,
+        paths_addr: Addr,
+        sender: &str,
+        discounts: Vec<HolderDiscount>,
+    ) -> anyhow::Result<AppResponse> {
+        let msg = ExecuteMsg::UpdateDiscounts { discounts };
+        app.execute_contract(Addr::unchecked(sender), paths_addr, &msg, &[])
+    }
+
+    fn update_referral_config(
+        app: &mut App,
+        paths_addr: Addr,
+        sender: &str,
+        referral_config: Option<ReferralConfig>,
+    ) -> anyhow::Result<AppResponse> {
+        let msg = ExecuteMsg::UpdateReferralConfig { referral_config };
+        app.execute_contract(Addr::unchecked(sender), paths_addr, &msg, &[])
+    }
+
+    fn claim_referral_rewards(
+        app: &mut App,
+        paths_addr: Addr,
+        sender: &str,
+    ) -> anyhow::Result<AppResponse> {
+        let msg = ExecuteMsg::ClaimReferralRewards {};
+        app.execute_contract(Addr::unchecked(sender), paths_addr, &msg, &[])
+    }
+
+    fn update_burn_share(
+        app: &mut App,
+        paths_addr: Addr,
+        sender: &str,
+        burn_share: Option<Decimal>,
+    ) -> anyhow::Result<AppResponse> {
+        let msg = ExecuteMsg::UpdateBurnShare { burn_share };
+        app.execute_contract(Addr::unchecked(sender), paths_addr, &msg, &[])
+    }
+
+    fn update_platform_fee(
+        app: &mut App,
+        paths_addr: Addr,
+        sender: &str,
+        fee_collector: &str,
+        rate: Decimal,
+    ) -> anyhow::Result<AppResponse> {
+        let msg = ExecuteMsg::UpdatePlatformFee {
+            fee_collector: fee_collector.to_string(),
+            rate,
+        };
+        app.execute_contract(Addr::unchecked(sender), paths_addr, &msg, &[])
+    }
+
+    fn update_reserved_paths(
+        app: &mut App,
+        paths_addr: Addr,
+        sender: &str,
+        add: Vec<String>,
+        remove: Vec<String>,
+    ) -> anyhow::Result<AppResponse> {
+        let msg = ExecuteMsg::UpdateReservedPaths { add, remove };
+        app.execute_contract(Addr::unchecked(sender), paths_addr, &msg, &[])
+    }
+
+    // Value of an attribute on the first event of the given type
+    fn event_attribute(resp: &AppResponse, ty: &str, key: &str) -> Option<String> {
+        resp.events
+            .iter()
+            .find(|event| event.ty == ty)?
+            .attributes
+            .iter()
+            .find(|attr| attr.key == key)
+            .map(|attr| attr.value.clone())
+    }
+
     fn get_config(app: &mut App, paths_addr: Addr) -> Config {
         app.wrap()
             .query_wasm_smart(paths_addr, &QueryMsg::Config {})
@@ -304,6 +441,74 @@ mod tests {
             .unwrap()
     }
 
+    fn get_can_mint(app: &mut App, paths_addr: Addr, address: &str) -> CanMintResponse {
+        app.wrap()
+            .query_wasm_smart(
+                paths_addr,
+                &QueryMsg::CanMint {
+                    address: address.to_string(),
+                },
+            )
+            .unwrap()
+    }
+
+    fn get_quote_price(
+        app: &mut App,
+        paths_addr: Addr,
+        address: &str,
+        path: &str,
+    ) -> QuotePriceResponse {
+        app.wrap()
+            .query_wasm_smart(
+                paths_addr,
+                &QueryMsg::QuotePrice {
+                    address: address.to_string(),
+                    path: path.to_string(),
+                },
+            )
+            .unwrap()
+    }
+
+    fn get_referrer_stats(app: &mut App, paths_addr: Addr, address: &str) -> ReferrerStatsResponse {
+        app.wrap()
+            .query_wasm_smart(
+                paths_addr,
+                &QueryMsg::ReferrerStats {
+                    address: address.to_string(),
+                },
+            )
+            .unwrap()
+    }
+
+    fn get_burned(app: &mut App, paths_addr: Addr) -> BurnedResponse {
+        app.wrap()
+            .query_wasm_smart(paths_addr, &QueryMsg::Burned {})
+            .unwrap()
+    }
+
+    fn get_platform_fee(app: &mut App, paths_addr: Addr) -> PlatformFeeResponse {
+        app.wrap()
+            .query_wasm_smart(paths_addr, &QueryMsg::PlatformFee {})
+            .unwrap()
+    }
+
+    fn get_path_available(app: &mut App, paths_addr: Addr, path: &str) -> PathAvailableResponse {
+        app.wrap()
+            .query_wasm_smart(
+                paths_addr,
+                &QueryMsg::PathAvailable {
+                    path: path.to_string(),
+                },
+            )
+            .unwrap()
+    }
+
+    fn get_info(app: &mut App, paths_addr: Addr) -> InfoResponse {
+        app.wrap()
+            .query_wasm_smart(paths_addr, &QueryMsg::Info {})
+            .unwrap()
+    }
+
     fn get_cw20_balance(app: &mut App, cw20_addr: Addr, address: &str) -> BalanceResponse {
         app.wrap()
             .query_wasm_smart(
@@ -411,6 +616,29 @@ mod tests {
         assert_eq!(config.token_id, Some(token_id));
     }
 
+    #[test]
+    fn test_receive_root_name_with_message() {
+        let mut app = mock_app();
+        let (whoami, paths) = setup_test_case(&mut app, None);
+
+        // Mint the name
+        let token_id = "root_name".to_string();
+        mint_name(&mut app, whoami.clone(), ADMIN, &token_id).unwrap();
+
+        // Transfer to the contract with a memo attached, it is still taken as the root
+        let msg = whoami::msg::ExecuteMsg::SendNft {
+            contract: paths.to_string(),
+            token_id: token_id.clone(),
+            msg: to_binary(&"memo").unwrap(),
+        };
+        app.execute_contract(Addr::unchecked(ADMIN), whoami, &msg, &[])
+            .unwrap();
+
+        // Check config, name is Some("root_name")
+        let config = get_config(&mut app, paths);
+        assert_eq!(config.token_id, Some(token_id));
+    }
+
     #[test]
     #[should_panic(expected = "Unauthorized")]
     fn test_receive_root_name_invalid_nft_contract() {
@@ -630,6 +858,40 @@ mod tests {
             assert_eq!(resp.owner, USER.to_string());
         }
 
+        #[test]
+        fn test_mint_path_taken_returns_payment() {
+            let mut app = mock_app();
+            let (_whoami, paths, _token_id) = setup_test_case_with_name(
+                &mut app,
+                Some(PaymentDetails::Native {
+                    denom: NATIVE_DENOM.to_string(),
+                    amount: Uint128::new(100),
+                }),
+            );
+
+            mint_path_native(&mut app, paths.clone(), USER, "a", coins(100, NATIVE_DENOM)).unwrap();
+
+            let user_balance_before = app
+                .wrap()
+                .query_balance(Addr::unchecked(USER), NATIVE_DENOM.to_string())
+                .unwrap();
+
+            let err =
+                mint_path_native(&mut app, paths.clone(), USER, "a", coins(100, NATIVE_DENOM))
+                    .unwrap_err();
+            assert!(format!("{:?}", err).contains("Path a is already taken"));
+
+            // The failed mint is rolled back so the payment never leaves the minter
+            let user_balance = app
+                .wrap()
+                .query_balance(Addr::unchecked(USER), NATIVE_DENOM.to_string())
+                .unwrap();
+            assert_eq!(user_balance, user_balance_before);
+
+            let resp = get_payment_details_balance(&mut app, paths);
+            assert_eq!(resp.amount, Uint128::new(100));
+        }
+
         #[test]
         #[should_panic(expected = "Must send reserve token 'ujunox'")]
         fn test_mint_path_invalid_denom() {
@@ -870,7 +1132,7 @@ mod tests {
         }
 
         #[test]
-        #[should_panic(expected = "Unauthorized")]
+        #[should_panic(expected = "This message does no accept funds")]
         fn test_mint_path_pay_native() {
             let mut app = mock_app();
             let cw20_addr = instantiate_cw20(&mut app);
@@ -997,6 +1259,16 @@ mod tests {
             assert_eq!(resp.owner, USER.to_string());
         }
 
+        #[test]
+        #[should_panic(expected = "Path a is already taken")]
+        fn test_mint_path_taken() {
+            let mut app = mock_app();
+            let (_whoami, paths, _token_id) = setup_test_case_with_name(&mut app, None);
+
+            mint_path_native(&mut app, paths.clone(), USER, "a", vec![]).unwrap();
+            mint_path_native(&mut app, paths, ADMIN, "a", vec![]).unwrap();
+        }
+
         #[test]
         #[should_panic(expected = "This message does no accept funds")]
         fn test_mint_path_pay_native() {
@@ -1061,4 +1333,4562 @@ mod tests {
             withdraw_payments(&mut app, paths, USER).unwrap();
         }
     }
+
+    mod gating {
+        use crate::integration_tests::tests::{
+            get_can_mint, get_nft_owner, instantiate_cw20, mint_name, mint_path_native, mock_app,
+            setup_test_case_with_name, update_gating_rules, ADMIN, OUTSIDER, USER,
+        };
+        use crate::msg::HolderRule;
+        use cosmwasm_std::Uint128;
+
+        #[test]
+        fn test_mint_path_cw20_holder() {
+            let mut app = mock_app();
+            let cw20_addr = instantiate_cw20(&mut app);
+            let (whoami, paths, token_id) = setup_test_case_with_name(&mut app, None);
+
+            update_gating_rules(
+                &mut app,
+                paths.clone(),
+                ADMIN,
+                vec![HolderRule::Cw20Balance {
+                    token_address: cw20_addr.to_string(),
+                    min_balance: Uint128::new(100),
+                }],
+            )
+            .unwrap();
+
+            let path = "a".to_string();
+            mint_path_native(&mut app, paths, USER, &path, vec![]).unwrap();
+
+            let resp = get_nft_owner(&mut app, whoami, format!("{}::{}", token_id, path));
+            assert_eq!(resp.owner, USER.to_string());
+        }
+
+        #[test]
+        #[should_panic(expected = "Address does not meet any of the requirements to mint a path")]
+        fn test_mint_path_cw20_non_holder() {
+            let mut app = mock_app();
+            let cw20_addr = instantiate_cw20(&mut app);
+            let (_whoami, paths, _token_id) = setup_test_case_with_name(&mut app, None);
+
+            update_gating_rules(
+                &mut app,
+                paths.clone(),
+                ADMIN,
+                vec![HolderRule::Cw20Balance {
+                    token_address: cw20_addr.to_string(),
+                    min_balance: Uint128::new(100),
+                }],
+            )
+            .unwrap();
+
+            mint_path_native(&mut app, paths, OUTSIDER, "a", vec![]).unwrap();
+        }
+
+        #[test]
+        fn test_can_mint_cw721_holder() {
+            let mut app = mock_app();
+            let cw20_addr = instantiate_cw20(&mut app);
+            let (whoami, paths, _token_id) = setup_test_case_with_name(&mut app, None);
+
+            // USER holds a name in the whoami collection, OUTSIDER does not
+            mint_name(&mut app, whoami.clone(), USER, "user_name").unwrap();
+
+            let rules = vec![
+                HolderRule::Cw20Balance {
+                    token_address: cw20_addr.to_string(),
+                    min_balance: Uint128::new(100),
+                },
+                HolderRule::Cw721Ownership {
+                    collection_address: whoami.to_string(),
+                },
+            ];
+            update_gating_rules(&mut app, paths.clone(), ADMIN, rules.clone()).unwrap();
+
+            let resp = get_can_mint(&mut app, paths.clone(), USER);
+            assert!(resp.can_mint);
+            assert!(resp.failed_rules.is_empty());
+
+            let resp = get_can_mint(&mut app, paths, OUTSIDER);
+            assert!(!resp.can_mint);
+            assert_eq!(resp.failed_rules.len(), 2);
+            assert_eq!(resp.failed_rules[0].rule, rules[0]);
+            assert_eq!(resp.failed_rules[1].rule, rules[1]);
+        }
+
+        #[test]
+        #[should_panic(expected = "Unauthorized")]
+        fn test_update_gating_rules_non_admin() {
+            let mut app = mock_app();
+            let (_whoami, paths, _token_id) = setup_test_case_with_name(&mut app, None);
+
+            update_gating_rules(&mut app, paths, USER, vec![]).unwrap();
+        }
+    }
+
+    mod discounts {
+        use crate::integration_tests::tests::{
+            get_nft_owner, get_payment_details_balance, get_quote_price, instantiate_cw20,
+            mint_name, mint_path_native, mock_app, setup_test_case_with_name, update_discounts,
+            ADMIN, NATIVE_DENOM, OUTSIDER, USER,
+        };
+        use crate::msg::{HolderDiscount, HolderRule, PaymentDetails};
+        use cosmwasm_std::{coins, Decimal, Uint128};
+
+        #[test]
+        fn test_mint_path_with_discount() {
+            let mut app = mock_app();
+            let cw20_addr = instantiate_cw20(&mut app);
+            let (whoami, paths, token_id) = setup_test_case_with_name(
+                &mut app,
+                Some(PaymentDetails::Native {
+                    denom: NATIVE_DENOM.to_string(),
+                    amount: Uint128::new(100),
+                }),
+            );
+
+            update_discounts(
+                &mut app,
+                paths.clone(),
+                ADMIN,
+                vec![HolderDiscount {
+                    priority: 1,
+                    rule: HolderRule::Cw20Balance {
+                        token_address: cw20_addr.to_string(),
+                        min_balance: Uint128::new(1),
+                    },
+                    discount: Decimal::percent(10),
+                }],
+            )
+            .unwrap();
+
+            let resp = get_quote_price(&mut app, paths.clone(), USER, "a");
+            assert_eq!(resp.base_amount, Uint128::new(100));
+            assert_eq!(resp.discount, Some(Decimal::percent(10)));
+            assert_eq!(resp.amount, Uint128::new(90));
+
+            let resp = get_quote_price(&mut app, paths.clone(), OUTSIDER, "a");
+            assert_eq!(resp.discount, None);
+            assert_eq!(resp.amount, Uint128::new(100));
+
+            let path = "a".to_string();
+            mint_path_native(
+                &mut app,
+                paths.clone(),
+                USER,
+                &path,
+                coins(90, NATIVE_DENOM),
+            )
+            .unwrap();
+
+            let resp = get_nft_owner(&mut app, whoami, format!("{}::{}", token_id, path));
+            assert_eq!(resp.owner, USER.to_string());
+
+            let resp = get_payment_details_balance(&mut app, paths);
+            assert_eq!(resp.amount, Uint128::new(90));
+        }
+
+        #[test]
+        #[should_panic(expected = "Insufficient funds sent to mint a path")]
+        fn test_mint_path_full_price_with_discount() {
+            let mut app = mock_app();
+            let cw20_addr = instantiate_cw20(&mut app);
+            let (_whoami, paths, _token_id) = setup_test_case_with_name(
+                &mut app,
+                Some(PaymentDetails::Native {
+                    denom: NATIVE_DENOM.to_string(),
+                    amount: Uint128::new(100),
+                }),
+            );
+
+            update_discounts(
+                &mut app,
+                paths.clone(),
+                ADMIN,
+                vec![HolderDiscount {
+                    priority: 1,
+                    rule: HolderRule::Cw20Balance {
+                        token_address: cw20_addr.to_string(),
+                        min_balance: Uint128::new(1),
+                    },
+                    discount: Decimal::percent(10),
+                }],
+            )
+            .unwrap();
+
+            mint_path_native(&mut app, paths, USER, "a", coins(100, NATIVE_DENOM)).unwrap();
+        }
+
+        #[test]
+        fn test_highest_priority_discount_applies() {
+            let mut app = mock_app();
+            let cw20_addr = instantiate_cw20(&mut app);
+            let (whoami, paths, _token_id) = setup_test_case_with_name(
+                &mut app,
+                Some(PaymentDetails::Native {
+                    denom: NATIVE_DENOM.to_string(),
+                    amount: Uint128::new(100),
+                }),
+            );
+            mint_name(&mut app, whoami.clone(), USER, "user_name").unwrap();
+
+            update_discounts(
+                &mut app,
+                paths.clone(),
+                ADMIN,
+                vec![
+                    HolderDiscount {
+                        priority: 1,
+                        rule: HolderRule::Cw20Balance {
+                            token_address: cw20_addr.to_string(),
+                            min_balance: Uint128::new(1),
+                        },
+                        discount: Decimal::percent(10),
+                    },
+                    HolderDiscount {
+                        priority: 2,
+                        rule: HolderRule::Cw721Ownership {
+                            collection_address: whoami.to_string(),
+                        },
+                        discount: Decimal::percent(25),
+                    },
+                ],
+            )
+            .unwrap();
+
+            let resp = get_quote_price(&mut app, paths, USER, "a");
+            assert_eq!(resp.discount, Some(Decimal::percent(25)));
+            assert_eq!(resp.amount, Uint128::new(75));
+        }
+
+        #[test]
+        #[should_panic(expected = "Discounts must be less than 100%")]
+        fn test_update_discounts_invalid() {
+            let mut app = mock_app();
+            let cw20_addr = instantiate_cw20(&mut app);
+            let (_whoami, paths, _token_id) = setup_test_case_with_name(&mut app, None);
+
+            update_discounts(
+                &mut app,
+                paths,
+                ADMIN,
+                vec![HolderDiscount {
+                    priority: 1,
+                    rule: HolderRule::Cw20Balance {
+                        token_address: cw20_addr.to_string(),
+                        min_balance: Uint128::new(1),
+                    },
+                    discount: Decimal::one(),
+                }],
+            )
+            .unwrap();
+        }
+    }
+
+    mod referrals {
+        use crate::integration_tests::tests::{
+            claim_referral_rewards, get_cw20_balance, get_payment_details_balance,
+            get_referrer_stats, instantiate_cw20, mint_path_cw20_referred,
+            mint_path_native_referred, mock_app, setup_test_case_with_name, update_referral_config,
+            withdraw_payments, ADMIN, NATIVE_DENOM, OUTSIDER, USER,
+        };
+        use crate::msg::{PaymentDetails, ReferralConfig, ReferralPayout};
+        use cosmwasm_std::{coins, Addr, Decimal, Uint128};
+
+        #[test]
+        fn test_mint_path_forward_referral() {
+            let mut app = mock_app();
+            let (_whoami, paths, _token_id) = setup_test_case_with_name(
+                &mut app,
+                Some(PaymentDetails::Native {
+                    denom: NATIVE_DENOM.to_string(),
+                    amount: Uint128::new(100),
+                }),
+            );
+
+            update_referral_config(
+                &mut app,
+                paths.clone(),
+                ADMIN,
+                Some(ReferralConfig {
+                    share: Decimal::percent(10),
+                    payout: ReferralPayout::Forward,
+                }),
+            )
+            .unwrap();
+
+            mint_path_native_referred(
+                &mut app,
+                paths.clone(),
+                USER,
+                "a",
+                Some(OUTSIDER),
+                coins(100, NATIVE_DENOM),
+            )
+            .unwrap();
+
+            let referrer_balance = app
+                .wrap()
+                .query_balance(Addr::unchecked(OUTSIDER), NATIVE_DENOM.to_string())
+                .unwrap();
+            assert_eq!(referrer_balance.amount, Uint128::new(10));
+
+            let resp = get_payment_details_balance(&mut app, paths.clone());
+            assert_eq!(resp.amount, Uint128::new(90));
+
+            let resp = get_referrer_stats(&mut app, paths, OUTSIDER);
+            assert_eq!(resp.referrals, 1);
+            assert_eq!(resp.total_earned, Uint128::new(10));
+            assert!(resp.claimable.is_zero());
+        }
+
+        #[test]
+        fn test_mint_path_accrue_referral() {
+            let mut app = mock_app();
+            let cw20_addr = instantiate_cw20(&mut app);
+            let (_whoami, paths, _token_id) = setup_test_case_with_name(
+                &mut app,
+                Some(PaymentDetails::Cw20 {
+                    token_address: cw20_addr.to_string(),
+                    amount: Uint128::new(100),
+                }),
+            );
+
+            update_referral_config(
+                &mut app,
+                paths.clone(),
+                ADMIN,
+                Some(ReferralConfig {
+                    share: Decimal::percent(10),
+                    payout: ReferralPayout::Accrue,
+                }),
+            )
+            .unwrap();
+
+            mint_path_cw20_referred(
+                &mut app,
+                cw20_addr.clone(),
+                paths.clone(),
+                USER,
+                Uint128::new(100),
+                "a",
+                Some(OUTSIDER),
+            )
+            .unwrap();
+
+            let resp = get_referrer_stats(&mut app, paths.clone(), OUTSIDER);
+            assert_eq!(resp.referrals, 1);
+            assert_eq!(resp.claimable, Uint128::new(10));
+
+            // Rewards owed to the referrer are not collectable by the admin
+            let resp = get_payment_details_balance(&mut app, paths.clone());
+            assert_eq!(resp.amount, Uint128::new(90));
+
+            let admin_balance_before = get_cw20_balance(&mut app, cw20_addr.clone(), ADMIN);
+            withdraw_payments(&mut app, paths.clone(), ADMIN).unwrap();
+            let admin_balance = get_cw20_balance(&mut app, cw20_addr.clone(), ADMIN);
+            assert_eq!(
+                admin_balance.balance,
+                admin_balance_before.balance + Uint128::new(90)
+            );
+
+            claim_referral_rewards(&mut app, paths.clone(), OUTSIDER).unwrap();
+            let referrer_balance = get_cw20_balance(&mut app, cw20_addr, OUTSIDER);
+            assert_eq!(referrer_balance.balance, Uint128::new(10));
+
+            let resp = get_referrer_stats(&mut app, paths, OUTSIDER);
+            assert!(resp.claimable.is_zero());
+            assert_eq!(resp.total_earned, Uint128::new(10));
+        }
+
+        #[test]
+        #[should_panic(expected = "You cannot refer yourself")]
+        fn test_mint_path_self_referral() {
+            let mut app = mock_app();
+            let (_whoami, paths, _token_id) = setup_test_case_with_name(
+                &mut app,
+                Some(PaymentDetails::Native {
+                    denom: NATIVE_DENOM.to_string(),
+                    amount: Uint128::new(100),
+                }),
+            );
+
+            mint_path_native_referred(
+                &mut app,
+                paths,
+                USER,
+                "a",
+                Some(USER),
+                coins(100, NATIVE_DENOM),
+            )
+            .unwrap();
+        }
+
+        #[test]
+        #[should_panic(expected = "No referral rewards are available to claim")]
+        fn test_claim_referral_rewards_none() {
+            let mut app = mock_app();
+            let (_whoami, paths, _token_id) = setup_test_case_with_name(
+                &mut app,
+                Some(PaymentDetails::Native {
+                    denom: NATIVE_DENOM.to_string(),
+                    amount: Uint128::new(100),
+                }),
+            );
+
+            claim_referral_rewards(&mut app, paths, OUTSIDER).unwrap();
+        }
+    }
+
+    mod burning {
+        use crate::integration_tests::tests::{
+            get_burned, get_payment_details_balance, instantiate_cw20, mint_path_cw20,
+            mint_path_native, mock_app, setup_test_case_with_name, update_burn_share,
+            update_referral_config, ADMIN, NATIVE_DENOM, USER,
+        };
+        use crate::msg::{PaymentDetails, ReferralConfig, ReferralPayout};
+        use cosmwasm_std::{coins, Decimal, Uint128};
+        use cw20::{Cw20QueryMsg, TokenInfoResponse};
+
+        #[test]
+        fn test_mint_path_burns_native() {
+            let mut app = mock_app();
+            let (_whoami, paths, _token_id) = setup_test_case_with_name(
+                &mut app,
+                Some(PaymentDetails::Native {
+                    denom: NATIVE_DENOM.to_string(),
+                    amount: Uint128::new(100),
+                }),
+            );
+
+            update_burn_share(&mut app, paths.clone(), ADMIN, Some(Decimal::percent(10))).unwrap();
+
+            mint_path_native(&mut app, paths.clone(), USER, "a", coins(100, NATIVE_DENOM)).unwrap();
+
+            // The contract only keeps what was not burned
+            let contract_balance = app
+                .wrap()
+                .query_balance(paths.clone(), NATIVE_DENOM.to_string())
+                .unwrap();
+            assert_eq!(contract_balance.amount, Uint128::new(90));
+
+            let resp = get_burned(&mut app, paths);
+            assert_eq!(resp.burn_share, Some(Decimal::percent(10)));
+            assert_eq!(resp.total_burned, Uint128::new(10));
+        }
+
+        #[test]
+        fn test_mint_path_burns_cw20() {
+            let mut app = mock_app();
+            let cw20_addr = instantiate_cw20(&mut app);
+            let (_whoami, paths, _token_id) = setup_test_case_with_name(
+                &mut app,
+                Some(PaymentDetails::Cw20 {
+                    token_address: cw20_addr.to_string(),
+                    amount: Uint128::new(100),
+                }),
+            );
+
+            update_burn_share(&mut app, paths.clone(), ADMIN, Some(Decimal::percent(25))).unwrap();
+
+            mint_path_cw20(
+                &mut app,
+                cw20_addr.clone(),
+                paths.clone(),
+                USER,
+                Uint128::new(100),
+                "a",
+            )
+            .unwrap();
+            mint_path_cw20(
+                &mut app,
+                cw20_addr.clone(),
+                paths.clone(),
+                USER,
+                Uint128::new(100),
+                "b",
+            )
+            .unwrap();
+
+            let token_info: TokenInfoResponse = app
+                .wrap()
+                .query_wasm_smart(cw20_addr, &Cw20QueryMsg::TokenInfo {})
+                .unwrap();
+            assert_eq!(token_info.total_supply, Uint128::new(19999950));
+
+            let resp = get_payment_details_balance(&mut app, paths.clone());
+            assert_eq!(resp.amount, Uint128::new(150));
+
+            let resp = get_burned(&mut app, paths);
+            assert_eq!(resp.total_burned, Uint128::new(50));
+        }
+
+        #[test]
+        #[should_panic(expected = "Payment shares cannot add up to more than 100%")]
+        fn test_update_burn_share_exceeds_total() {
+            let mut app = mock_app();
+            let (_whoami, paths, _token_id) = setup_test_case_with_name(
+                &mut app,
+                Some(PaymentDetails::Native {
+                    denom: NATIVE_DENOM.to_string(),
+                    amount: Uint128::new(100),
+                }),
+            );
+
+            update_referral_config(
+                &mut app,
+                paths.clone(),
+                ADMIN,
+                Some(ReferralConfig {
+                    share: Decimal::percent(60),
+                    payout: ReferralPayout::Forward,
+                }),
+            )
+            .unwrap();
+            update_burn_share(&mut app, paths, ADMIN, Some(Decimal::percent(50))).unwrap();
+        }
+
+        #[test]
+        #[should_panic(expected = "Unauthorized")]
+        fn test_update_burn_share_non_admin() {
+            let mut app = mock_app();
+            let (_whoami, paths, _token_id) = setup_test_case_with_name(&mut app, None);
+
+            update_burn_share(&mut app, paths, USER, Some(Decimal::percent(10))).unwrap();
+        }
+    }
+
+    mod platform_fee {
+        use crate::integration_tests::tests::{
+            get_cw20_balance, get_payment_details_balance, get_platform_fee, instantiate_cw20,
+            mint_path_cw20, mint_path_native, mock_app, setup_test_case_with_platform_fee,
+            transfer_root_name, update_burn_share, update_platform_fee, ADMIN, FEE_COLLECTOR,
+            NATIVE_DENOM, USER,
+        };
+        use crate::msg::{PaymentDetails, PlatformFee};
+        use cosmwasm_std::{coins, Addr, Decimal, Uint128};
+
+        #[test]
+        fn test_mint_path_native_platform_fee() {
+            let mut app = mock_app();
+            let (whoami, paths) = setup_test_case_with_platform_fee(
+                &mut app,
+                Some(PaymentDetails::Native {
+                    denom: NATIVE_DENOM.to_string(),
+                    amount: Uint128::new(100),
+                }),
+                Some(PlatformFee {
+                    fee_collector: FEE_COLLECTOR.to_string(),
+                    rate: Decimal::percent(5),
+                }),
+            );
+            transfer_root_name(&mut app, whoami, paths.clone());
+
+            mint_path_native(&mut app, paths.clone(), USER, "a", coins(100, NATIVE_DENOM)).unwrap();
+
+            let collector_balance = app
+                .wrap()
+                .query_balance(Addr::unchecked(FEE_COLLECTOR), NATIVE_DENOM.to_string())
+                .unwrap();
+            assert_eq!(collector_balance.amount, Uint128::new(5));
+
+            let resp = get_payment_details_balance(&mut app, paths);
+            assert_eq!(resp.amount, Uint128::new(95));
+        }
+
+        #[test]
+        fn test_mint_path_cw20_platform_fee() {
+            let mut app = mock_app();
+            let cw20_addr = instantiate_cw20(&mut app);
+            let (whoami, paths) = setup_test_case_with_platform_fee(
+                &mut app,
+                Some(PaymentDetails::Cw20 {
+                    token_address: cw20_addr.to_string(),
+                    amount: Uint128::new(100),
+                }),
+                Some(PlatformFee {
+                    fee_collector: FEE_COLLECTOR.to_string(),
+                    rate: Decimal::percent(5),
+                }),
+            );
+            transfer_root_name(&mut app, whoami, paths.clone());
+
+            mint_path_cw20(
+                &mut app,
+                cw20_addr.clone(),
+                paths.clone(),
+                USER,
+                Uint128::new(100),
+                "a",
+            )
+            .unwrap();
+
+            let collector_balance = get_cw20_balance(&mut app, cw20_addr, FEE_COLLECTOR);
+            assert_eq!(collector_balance.balance, Uint128::new(5));
+
+            let resp = get_payment_details_balance(&mut app, paths);
+            assert_eq!(resp.amount, Uint128::new(95));
+        }
+
+        #[test]
+        fn test_update_platform_fee_by_collector() {
+            let mut app = mock_app();
+            let (_whoami, paths) = setup_test_case_with_platform_fee(
+                &mut app,
+                None,
+                Some(PlatformFee {
+                    fee_collector: FEE_COLLECTOR.to_string(),
+                    rate: Decimal::percent(5),
+                }),
+            );
+
+            update_platform_fee(
+                &mut app,
+                paths.clone(),
+                FEE_COLLECTOR,
+                USER,
+                Decimal::percent(2),
+            )
+            .unwrap();
+
+            let resp = get_platform_fee(&mut app, paths).platform_fee.unwrap();
+            assert_eq!(resp.fee_collector, Addr::unchecked(USER));
+            assert_eq!(resp.rate, Decimal::percent(2));
+        }
+
+        #[test]
+        #[should_panic(expected = "Unauthorized")]
+        fn test_update_platform_fee_by_admin() {
+            let mut app = mock_app();
+            let (_whoami, paths) = setup_test_case_with_platform_fee(
+                &mut app,
+                None,
+                Some(PlatformFee {
+                    fee_collector: FEE_COLLECTOR.to_string(),
+                    rate: Decimal::percent(5),
+                }),
+            );
+
+            update_platform_fee(&mut app, paths, ADMIN, ADMIN, Decimal::zero()).unwrap();
+        }
+
+        #[test]
+        #[should_panic(expected = "Payment shares cannot add up to more than 100%")]
+        fn test_update_burn_share_exceeds_platform_fee() {
+            let mut app = mock_app();
+            let (_whoami, paths) = setup_test_case_with_platform_fee(
+                &mut app,
+                None,
+                Some(PlatformFee {
+                    fee_collector: FEE_COLLECTOR.to_string(),
+                    rate: Decimal::percent(60),
+                }),
+            );
+
+            update_burn_share(&mut app, paths, ADMIN, Some(Decimal::percent(50))).unwrap();
+        }
+    }
+
+    mod migration {
+        use crate::integration_tests::tests::{
+            contract_whoami_paths, get_config, get_info, instantiate_cw20, mock_app,
+            setup_test_case_with_name, ADMIN,
+        };
+        use crate::msg::{MigrateMsg, PaymentDetails};
+        use crate::state::{Config, CONFIG, PAYMENT_DETAILS};
+        use cosmwasm_std::{
+            to_binary, Addr, Binary, Deps, DepsMut, Empty, Env, MessageInfo, Response, StdResult,
+            Uint128,
+        };
+        use cw2::{set_contract_version, ContractVersion};
+        use cw_multi_test::{App, Contract, ContractWrapper, Executor};
+
+        fn execute_noop(
+            _deps: DepsMut,
+            _env: Env,
+            _info: MessageInfo,
+            _msg: Empty,
+        ) -> StdResult<Response> {
+            Ok(Response::new())
+        }
+
+        fn query_noop(_deps: Deps, _env: Env, _msg: Empty) -> StdResult<Binary> {
+            to_binary(&Empty {})
+        }
+
+        // Stands in for a deployment of the 1.0.0 release
+        fn instantiate_v1_0(
+            deps: DepsMut,
+            _env: Env,
+            _info: MessageInfo,
+            _msg: Empty,
+        ) -> StdResult<Response> {
+            set_contract_version(deps.storage, "crates.io:whoami-paths", "1.0.0")?;
+            CONFIG.save(
+                deps.storage,
+                &Config {
+                    whoami_address: "whoami".to_string(),
+                    admin: Addr::unchecked(ADMIN),
+                    token_id: Some("root_name".to_string()),
+                },
+            )?;
+            PAYMENT_DETAILS.save(
+                deps.storage,
+                &PaymentDetails::Native {
+                    denom: "ujunox".to_string(),
+                    amount: Uint128::new(100),
+                },
+            )?;
+            Ok(Response::new())
+        }
+
+        // Stands in for a deployment of a release newer than this one
+        fn instantiate_future(
+            deps: DepsMut,
+            _env: Env,
+            _info: MessageInfo,
+            _msg: Empty,
+        ) -> StdResult<Response> {
+            set_contract_version(deps.storage, "crates.io:whoami-paths", "99.0.0")?;
+            Ok(Response::new())
+        }
+
+        fn instantiate_mock(app: &mut App, contract: Box<dyn Contract<Empty>>) -> Addr {
+            let code_id = app.store_code(contract);
+            app.instantiate_contract(
+                code_id,
+                Addr::unchecked(ADMIN),
+                &Empty {},
+                &[],
+                "mock",
+                Some(ADMIN.to_string()),
+            )
+            .unwrap()
+        }
+
+        fn migrate(app: &mut App, contract_addr: Addr) -> anyhow::Result<()> {
+            let code_id = app.store_code(contract_whoami_paths());
+            app.migrate_contract(
+                Addr::unchecked(ADMIN),
+                contract_addr,
+                &MigrateMsg {},
+                code_id,
+            )?;
+            Ok(())
+        }
+
+        fn get_contract_version(app: &mut App, contract_addr: Addr) -> ContractVersion {
+            let raw = app
+                .wrap()
+                .query_wasm_raw(contract_addr, b"contract_info".to_vec())
+                .unwrap()
+                .unwrap();
+            cosmwasm_std::from_slice(&raw).unwrap()
+        }
+
+        #[test]
+        fn test_migrate_same_version() {
+            let mut app = mock_app();
+            let (_whoami, paths, token_id) = setup_test_case_with_name(&mut app, None);
+
+            migrate(&mut app, paths.clone()).unwrap();
+
+            let config = get_config(&mut app, paths.clone());
+            assert_eq!(config.token_id, Some(token_id));
+            let version = get_contract_version(&mut app, paths);
+            assert_eq!(version.version, env!("CARGO_PKG_VERSION"));
+        }
+
+        #[test]
+        fn test_migrate_from_v1_0() {
+            let mut app = mock_app();
+            let paths = instantiate_mock(
+                &mut app,
+                Box::new(ContractWrapper::new(
+                    execute_noop,
+                    instantiate_v1_0,
+                    query_noop,
+                )),
+            );
+
+            migrate(&mut app, paths.clone()).unwrap();
+
+            let config = get_config(&mut app, paths.clone());
+            assert_eq!(config.admin, Addr::unchecked(ADMIN));
+            assert_eq!(config.token_id, Some("root_name".to_string()));
+            // Settings added since 1.0.0 read back as their defaults
+            let info = get_info(&mut app, paths.clone());
+            assert_eq!(info.minted_count, 0);
+            assert_eq!(
+                info.payment_details,
+                Some(PaymentDetails::Native {
+                    denom: "ujunox".to_string(),
+                    amount: Uint128::new(100),
+                })
+            );
+            let version = get_contract_version(&mut app, paths);
+            assert_eq!(version.contract, "crates.io:whoami-paths");
+            assert_eq!(version.version, env!("CARGO_PKG_VERSION"));
+        }
+
+        #[test]
+        #[should_panic(expected = "Cannot migrate from version 99.0.0 to older version")]
+        fn test_migrate_downgrade() {
+            let mut app = mock_app();
+            let paths = instantiate_mock(
+                &mut app,
+                Box::new(ContractWrapper::new(
+                    execute_noop,
+                    instantiate_future,
+                    query_noop,
+                )),
+            );
+
+            migrate(&mut app, paths).unwrap();
+        }
+
+        #[test]
+        #[should_panic(expected = "Cannot migrate from a different contract: crates.io:cw20-base")]
+        fn test_migrate_different_contract() {
+            let mut app = mock_app();
+            let cw20_addr = instantiate_cw20(&mut app);
+
+            migrate(&mut app, cw20_addr).unwrap();
+        }
+    }
+
+    mod path_availability {
+        use crate::integration_tests::tests::{
+            get_nft_owner, get_path_available, instantiate_cw20, mint_path_native, mock_app,
+            setup_test_case, setup_test_case_with_name, update_reserved_paths, ADMIN, USER,
+        };
+        use crate::msg::{PathAvailableResponse, PathStatus, QueryMsg};
+        use crate::state::{Config, CONFIG};
+        use cosmwasm_std::{Addr, DepsMut, Env, MessageInfo, Response, StdResult};
+        use cw_multi_test::{ContractWrapper, Executor};
+
+        // This contract with a root but pointed at something that isn't whoami
+        fn instantiate_wrong_whoami(
+            deps: DepsMut,
+            _env: Env,
+            _info: MessageInfo,
+            whoami_address: String,
+        ) -> StdResult<Response> {
+            CONFIG.save(
+                deps.storage,
+                &Config {
+                    whoami_address,
+                    admin: Addr::unchecked(ADMIN),
+                    token_id: Some("root_name".to_string()),
+                },
+            )?;
+            Ok(Response::new())
+        }
+
+        #[test]
+        fn test_path_available_query_error() {
+            let mut app = mock_app();
+            let cw20_addr = instantiate_cw20(&mut app);
+            let code_id = app.store_code(Box::new(ContractWrapper::new(
+                crate::contract::execute,
+                instantiate_wrong_whoami,
+                crate::contract::query,
+            )));
+            let paths = app
+                .instantiate_contract(
+                    code_id,
+                    Addr::unchecked(ADMIN),
+                    &cw20_addr.to_string(),
+                    &[],
+                    "paths",
+                    None,
+                )
+                .unwrap();
+
+            // A failed lookup isn't reported as the path being free
+            let err = app
+                .wrap()
+                .query_wasm_smart::<PathAvailableResponse>(
+                    paths,
+                    &QueryMsg::PathAvailable {
+                        path: "a".to_string(),
+                    },
+                )
+                .unwrap_err();
+            assert!(!err.to_string().ends_with("not found"));
+        }
+
+        #[test]
+        fn test_path_available() {
+            let mut app = mock_app();
+            let (_whoami, paths, token_id) = setup_test_case_with_name(&mut app, None);
+
+            let resp = get_path_available(&mut app, paths.clone(), "a");
+            assert_eq!(resp.token_id, Some(format!("{}::a", token_id)));
+            assert_eq!(resp.status, PathStatus::Available);
+
+            mint_path_native(&mut app, paths.clone(), USER, "a", vec![]).unwrap();
+
+            let resp = get_path_available(&mut app, paths, "a");
+            assert_eq!(
+                resp.status,
+                PathStatus::Taken {
+                    owner: USER.to_string()
+                }
+            );
+        }
+
+        #[test]
+        fn test_path_available_invalid() {
+            let mut app = mock_app();
+            let (_whoami, paths, _token_id) = setup_test_case_with_name(&mut app, None);
+
+            let resp = get_path_available(&mut app, paths, "Not::Valid");
+            assert_eq!(
+                resp.status,
+                PathStatus::Invalid {
+                    reason: "Path Not::Valid is not a valid name".to_string()
+                }
+            );
+        }
+
+        #[test]
+        fn test_path_available_no_root() {
+            let mut app = mock_app();
+            let (_whoami, paths) = setup_test_case(&mut app, None);
+
+            let resp = get_path_available(&mut app, paths, "a");
+            assert_eq!(resp.token_id, None);
+            assert_eq!(resp.status, PathStatus::NoRoot);
+        }
+
+        #[test]
+        fn test_reserved_path() {
+            let mut app = mock_app();
+            let (whoami, paths, token_id) = setup_test_case_with_name(&mut app, None);
+
+            update_reserved_paths(
+                &mut app,
+                paths.clone(),
+                ADMIN,
+                vec!["dao".to_string()],
+                vec![],
+            )
+            .unwrap();
+
+            let resp = get_path_available(&mut app, paths.clone(), "dao");
+            assert_eq!(resp.status, PathStatus::Reserved);
+
+            let err = mint_path_native(&mut app, paths.clone(), USER, "dao", vec![]).unwrap_err();
+            assert!(format!("{:?}", err).contains("Path dao is reserved"));
+
+            // The admin can still mint it
+            mint_path_native(&mut app, paths, ADMIN, "dao", vec![]).unwrap();
+            let resp = get_nft_owner(&mut app, whoami, format!("{}::dao", token_id));
+            assert_eq!(resp.owner, ADMIN.to_string());
+        }
+
+        #[test]
+        #[should_panic(expected = "Path A is not a valid name")]
+        fn test_mint_path_invalid() {
+            let mut app = mock_app();
+            let (_whoami, paths, _token_id) = setup_test_case_with_name(&mut app, None);
+
+            mint_path_native(&mut app, paths, USER, "A", vec![]).unwrap();
+        }
+    }
+
+    mod info {
+        use crate::integration_tests::tests::{
+            get_info, mint_path_native, mock_app, setup_test_case, setup_test_case_with_name,
+            ADMIN, NATIVE_DENOM, USER,
+        };
+        use crate::msg::PaymentDetails;
+        use cosmwasm_std::{coins, Uint128};
+
+        #[test]
+        fn test_info() {
+            let mut app = mock_app();
+            let payment_details = PaymentDetails::Native {
+                denom: NATIVE_DENOM.to_string(),
+                amount: Uint128::new(100),
+            };
+            let (whoami, paths, token_id) =
+                setup_test_case_with_name(&mut app, Some(payment_details.clone()));
+
+            let resp = get_info(&mut app, paths.clone());
+            assert_eq!(resp.token_id, Some(token_id));
+            assert_eq!(resp.whoami_address, whoami.to_string());
+            assert_eq!(resp.admin, ADMIN.to_string());
+            assert_eq!(resp.payment_details, Some(payment_details.clone()));
+            assert_eq!(resp.current_price, Some(payment_details));
+            assert_eq!(resp.collected_balance, Uint128::zero());
+            assert_eq!(resp.minted_count, 0);
+            assert_eq!(resp.contract_version.contract, "crates.io:whoami-paths");
+            assert_eq!(
+                resp.contract_version.version,
+                env!("CARGO_PKG_VERSION").to_string()
+            );
+
+            mint_path_native(&mut app, paths.clone(), USER, "a", coins(100, NATIVE_DENOM)).unwrap();
+            mint_path_native(&mut app, paths.clone(), USER, "b", coins(100, NATIVE_DENOM)).unwrap();
+            // A failed mint is rolled back and not counted
+            mint_path_native(&mut app, paths.clone(), USER, "a", coins(100, NATIVE_DENOM))
+                .unwrap_err();
+
+            let resp = get_info(&mut app, paths);
+            assert_eq!(resp.collected_balance, Uint128::new(200));
+            assert_eq!(resp.minted_count, 2);
+        }
+
+        #[test]
+        fn test_info_no_root() {
+            let mut app = mock_app();
+            let (_whoami, paths) = setup_test_case(&mut app, None);
+
+            let resp = get_info(&mut app, paths);
+            assert_eq!(resp.token_id, None);
+            assert_eq!(resp.payment_details, None);
+            assert_eq!(resp.current_price, None);
+            assert_eq!(resp.collected_balance, Uint128::zero());
+            assert_eq!(resp.minted_count, 0);
+        }
+    }
+
+    mod events {
+        use crate::integration_tests::tests::{
+            event_attribute, instantiate_cw20, mint_path_cw20, mint_path_native, mock_app,
+            setup_test_case_with_name, withdraw_payments, ADMIN, NATIVE_DENOM, USER,
+        };
+        use crate::msg::PaymentDetails;
+        use cosmwasm_std::{coins, Uint128};
+
+        #[test]
+        fn test_mint_event_native() {
+            let mut app = mock_app();
+            let (_whoami, paths, token_id) = setup_test_case_with_name(
+                &mut app,
+                Some(PaymentDetails::Native {
+                    denom: NATIVE_DENOM.to_string(),
+                    amount: Uint128::new(100),
+                }),
+            );
+
+            let resp =
+                mint_path_native(&mut app, paths.clone(), USER, "a", coins(100, NATIVE_DENOM))
+                    .unwrap();
+            let attr = |key| event_attribute(&resp, "wasm-whoami_paths_mint", key);
+            assert_eq!(attr("root"), Some(token_id.clone()));
+            assert_eq!(attr("path"), Some("a".to_string()));
+            assert_eq!(attr("token_id"), Some(format!("{}::a", token_id)));
+            assert_eq!(attr("payer"), Some(USER.to_string()));
+            assert_eq!(attr("recipient"), Some(USER.to_string()));
+            assert_eq!(attr("amount"), Some("100".to_string()));
+            assert_eq!(attr("denom"), Some(NATIVE_DENOM.to_string()));
+            assert_eq!(attr("cw20_address"), None);
+            assert_eq!(
+                attr("block_time"),
+                Some(app.block_info().time.seconds().to_string())
+            );
+
+            let resp = withdraw_payments(&mut app, paths, ADMIN).unwrap();
+            let attr = |key| event_attribute(&resp, "wasm-whoami_paths_withdraw_payments", key);
+            assert_eq!(attr("recipient"), Some(ADMIN.to_string()));
+            assert_eq!(attr("amount"), Some("100".to_string()));
+            assert_eq!(attr("denom"), Some(NATIVE_DENOM.to_string()));
+        }
+
+        #[test]
+        fn test_mint_event_cw20() {
+            let mut app = mock_app();
+            let cw20 = instantiate_cw20(&mut app);
+            let (_whoami, paths, token_id) = setup_test_case_with_name(
+                &mut app,
+                Some(PaymentDetails::Cw20 {
+                    token_address: cw20.to_string(),
+                    amount: Uint128::new(100),
+                }),
+            );
+
+            let resp = mint_path_cw20(&mut app, cw20.clone(), paths, USER, Uint128::new(100), "a")
+                .unwrap();
+            let attr = |key| event_attribute(&resp, "wasm-whoami_paths_mint", key);
+            assert_eq!(attr("token_id"), Some(format!("{}::a", token_id)));
+            assert_eq!(attr("payer"), Some(USER.to_string()));
+            assert_eq!(attr("amount"), Some("100".to_string()));
+            assert_eq!(attr("cw20_address"), Some(cw20.to_string()));
+            assert_eq!(attr("denom"), None);
+        }
+
+        #[test]
+        fn test_mint_event_free() {
+            let mut app = mock_app();
+            let (_whoami, paths, token_id) = setup_test_case_with_name(&mut app, None);
+
+            let resp = mint_path_native(&mut app, paths, USER, "a", vec![]).unwrap();
+            let attr = |key| event_attribute(&resp, "wasm-whoami_paths_mint", key);
+            assert_eq!(attr("token_id"), Some(format!("{}::a", token_id)));
+            assert_eq!(attr("amount"), Some("0".to_string()));
+            assert_eq!(attr("denom"), None);
+            assert_eq!(attr("cw20_address"), None);
+        }
+    }
+
+    mod hooks {
+        use crate::integration_tests::tests::{
+            mint_path_native, mock_app, setup_test_case_with_name, ADMIN, NATIVE_DENOM, USER,
+        };
+        use crate::msg::{ExecuteMsg, HookExecuteMsg, HooksResponse, PaymentDetails, QueryMsg};
+        use cosmwasm_std::{
+            coins, to_binary, Addr, Binary, Deps, DepsMut, Empty, Env, MessageInfo, Response,
+            StdResult, Uint128,
+        };
+        use cw_multi_test::{App, AppResponse, ContractWrapper, Executor};
+        use cw_storage_plus::Item;
+
+        const HOOK_CALLS: Item<Vec<HookExecuteMsg>> = Item::new("hook_calls");
+
+        // Stands in for a rewards contract, recording every hook it is sent
+        fn execute_receiver(
+            deps: DepsMut,
+            _env: Env,
+            _info: MessageInfo,
+            msg: HookExecuteMsg,
+        ) -> StdResult<Response> {
+            let mut calls = HOOK_CALLS.may_load(deps.storage)?.unwrap_or_default();
+            calls.push(msg);
+            HOOK_CALLS.save(deps.storage, &calls)?;
+            Ok(Response::new())
+        }
+
+        fn instantiate_receiver(
+            _deps: DepsMut,
+            _env: Env,
+            _info: MessageInfo,
+            _msg: Empty,
+        ) -> StdResult<Response> {
+            Ok(Response::new())
+        }
+
+        fn query_receiver(deps: Deps, _env: Env, _msg: Empty) -> StdResult<Binary> {
+            to_binary(&HOOK_CALLS.may_load(deps.storage)?.unwrap_or_default())
+        }
+
+        fn instantiate_hook_receiver(app: &mut App) -> Addr {
+            let code_id = app.store_code(Box::new(ContractWrapper::new(
+                execute_receiver,
+                instantiate_receiver,
+                query_receiver,
+            )));
+            app.instantiate_contract(
+                code_id,
+                Addr::unchecked(ADMIN),
+                &Empty {},
+                &[],
+                "hook receiver",
+                None,
+            )
+            .unwrap()
+        }
+
+        fn get_hook_calls(app: &mut App, receiver: Addr) -> Vec<HookExecuteMsg> {
+            app.wrap().query_wasm_smart(receiver, &Empty {}).unwrap()
+        }
+
+        fn add_hook(
+            app: &mut App,
+            paths_addr: Addr,
+            sender: &str,
+            addr: &Addr,
+        ) -> anyhow::Result<AppResponse> {
+            let msg = ExecuteMsg::AddHook {
+                addr: addr.to_string(),
+            };
+            app.execute_contract(Addr::unchecked(sender), paths_addr, &msg, &[])
+        }
+
+        fn remove_hook(
+            app: &mut App,
+            paths_addr: Addr,
+            sender: &str,
+            addr: &Addr,
+        ) -> anyhow::Result<AppResponse> {
+            let msg = ExecuteMsg::RemoveHook {
+                addr: addr.to_string(),
+            };
+            app.execute_contract(Addr::unchecked(sender), paths_addr, &msg, &[])
+        }
+
+        fn get_hooks(app: &mut App, paths_addr: Addr) -> HooksResponse {
+            app.wrap()
+                .query_wasm_smart(paths_addr, &QueryMsg::Hooks {})
+                .unwrap()
+        }
+
+        #[test]
+        fn test_mint_calls_hooks() {
+            let mut app = mock_app();
+            let (_whoami, paths, token_id) = setup_test_case_with_name(
+                &mut app,
+                Some(PaymentDetails::Native {
+                    denom: NATIVE_DENOM.to_string(),
+                    amount: Uint128::new(100),
+                }),
+            );
+            let receiver = instantiate_hook_receiver(&mut app);
+
+            add_hook(&mut app, paths.clone(), ADMIN, &receiver).unwrap();
+            let resp = get_hooks(&mut app, paths.clone());
+            assert_eq!(resp.hooks, vec![receiver.to_string()]);
+
+            mint_path_native(&mut app, paths.clone(), USER, "a", coins(100, NATIVE_DENOM)).unwrap();
+
+            let calls = get_hook_calls(&mut app, receiver.clone());
+            assert_eq!(
+                calls,
+                vec![HookExecuteMsg::PathMintedHook {
+                    root: token_id,
+                    path: "a".to_string(),
+                    owner: USER.to_string(),
+                    controller: None,
+                    amount: Uint128::new(100),
+                }]
+            );
+
+            // A failed mint never reaches the hooks
+            mint_path_native(&mut app, paths.clone(), USER, "a", coins(100, NATIVE_DENOM))
+                .unwrap_err();
+            assert_eq!(get_hook_calls(&mut app, receiver.clone()).len(), 1);
+
+            remove_hook(&mut app, paths.clone(), ADMIN, &receiver).unwrap();
+            let resp = get_hooks(&mut app, paths.clone());
+            assert!(resp.hooks.is_empty());
+
+            mint_path_native(&mut app, paths, USER, "b", coins(100, NATIVE_DENOM)).unwrap();
+            assert_eq!(get_hook_calls(&mut app, receiver).len(), 1);
+        }
+
+        #[test]
+        fn test_custodial_mint_hook() {
+            let mut app = mock_app();
+            let (_whoami, paths, token_id) = setup_test_case_with_name(&mut app, None);
+            let receiver = instantiate_hook_receiver(&mut app);
+            add_hook(&mut app, paths.clone(), ADMIN, &receiver).unwrap();
+            let msg = ExecuteMsg::UpdateCustodial { custodial: true };
+            app.execute_contract(Addr::unchecked(ADMIN), paths.clone(), &msg, &[])
+                .unwrap();
+
+            mint_path_native(&mut app, paths.clone(), USER, "a", vec![]).unwrap();
+
+            let calls = get_hook_calls(&mut app, receiver);
+            assert_eq!(
+                calls,
+                vec![HookExecuteMsg::PathMintedHook {
+                    root: token_id,
+                    path: "a".to_string(),
+                    owner: paths.to_string(),
+                    controller: Some(USER.to_string()),
+                    amount: Uint128::zero(),
+                }]
+            );
+        }
+
+        #[test]
+        fn test_add_hook_twice() {
+            let mut app = mock_app();
+            let (_whoami, paths, _token_id) = setup_test_case_with_name(&mut app, None);
+            let receiver = instantiate_hook_receiver(&mut app);
+
+            add_hook(&mut app, paths.clone(), ADMIN, &receiver).unwrap();
+            let err = add_hook(&mut app, paths, ADMIN, &receiver).unwrap_err();
+            assert!(format!("{:?}", err).contains("is already registered"));
+        }
+
+        #[test]
+        fn test_remove_unknown_hook() {
+            let mut app = mock_app();
+            let (_whoami, paths, _token_id) = setup_test_case_with_name(&mut app, None);
+            let receiver = instantiate_hook_receiver(&mut app);
+
+            let err = remove_hook(&mut app, paths, ADMIN, &receiver).unwrap_err();
+            assert!(format!("{:?}", err).contains("is not registered"));
+        }
+
+        #[test]
+        #[should_panic(expected = "Unauthorized")]
+        fn test_add_hook_unauthorized() {
+            let mut app = mock_app();
+            let (_whoami, paths, _token_id) = setup_test_case_with_name(&mut app, None);
+            let receiver = instantiate_hook_receiver(&mut app);
+
+            add_hook(&mut app, paths, USER, &receiver).unwrap();
+        }
+    }
+
+    mod sub_roots {
+        use crate::integration_tests::tests::{
+            event_attribute, get_nft_owner, instantiate_cw20, mint_name, mint_path_native,
+            mock_app, setup_test_case_with_name, ADMIN, NATIVE_DENOM, OUTSIDER, USER,
+        };
+        use crate::msg::{
+            ExecuteMsg, HolderRule, PaymentDetails, QueryMsg, ReceiveNftMsg, SubRootResponse,
+        };
+        use cosmwasm_std::{coins, to_binary, Addr, Coin, Uint128};
+        use cw_multi_test::{App, AppResponse, Executor};
+
+        fn register_sub_root(
+            app: &mut App,
+            whoami_addr: Addr,
+            paths_addr: Addr,
+            sender: &str,
+            token_id: &str,
+            payment_details: Option<PaymentDetails>,
+            gating_rules: Vec<HolderRule>,
+        ) -> anyhow::Result<AppResponse> {
+            let msg = whoami::msg::ExecuteMsg::SendNft {
+                contract: paths_addr.to_string(),
+                token_id: token_id.to_string(),
+                msg: to_binary(&ReceiveNftMsg::RegisterSubRoot {
+                    payment_details,
+                    gating_rules,
+                })?,
+            };
+            app.execute_contract(Addr::unchecked(sender), whoami_addr, &msg, &[])
+        }
+
+        fn mint_sub_path_native(
+            app: &mut App,
+            paths_addr: Addr,
+            sender: &str,
+            parent: &str,
+            path: &str,
+            payment: Vec<Coin>,
+        ) -> anyhow::Result<AppResponse> {
+            app.execute_contract(
+                Addr::unchecked(sender),
+                paths_addr,
+                &ExecuteMsg::MintPath {
+                    path: path.to_string(),
+                    referrer: None,
+                    parent: Some(parent.to_string()),
+                    max_price: None,
+                    allowlist: None,
+                },
+                &payment,
+            )
+        }
+
+        fn get_sub_root(app: &mut App, paths_addr: Addr, token_id: &str) -> SubRootResponse {
+            app.wrap()
+                .query_wasm_smart(
+                    paths_addr,
+                    &QueryMsg::SubRoot {
+                        token_id: token_id.to_string(),
+                    },
+                )
+                .unwrap()
+        }
+
+        #[test]
+        fn test_mint_under_sub_root() {
+            let mut app = mock_app();
+            let (whoami, paths, token_id) = setup_test_case_with_name(&mut app, None);
+            let team = format!("{}::team", token_id);
+
+            mint_path_native(&mut app, paths.clone(), USER, "team", vec![]).unwrap();
+            let sub_root_payment = PaymentDetails::Native {
+                denom: NATIVE_DENOM.to_string(),
+                amount: Uint128::new(50),
+            };
+            let resp = register_sub_root(
+                &mut app,
+                whoami.clone(),
+                paths.clone(),
+                USER,
+                &team,
+                Some(sub_root_payment.clone()),
+                vec![],
+            )
+            .unwrap();
+            let attr = |key| event_attribute(&resp, "wasm-whoami_paths_register_sub_root", key);
+            assert_eq!(attr("owner"), Some(USER.to_string()));
+            assert_eq!(attr("payer"), None);
+
+            let resp = get_sub_root(&mut app, paths.clone(), &team);
+            assert_eq!(resp.owner, USER.to_string());
+            assert_eq!(resp.payment_details, Some(sub_root_payment));
+
+            let user_balance_before = app.wrap().query_balance(USER, NATIVE_DENOM).unwrap().amount;
+
+            // Sub-roots charge their own price rather than the root's
+            let err = mint_sub_path_native(&mut app, paths.clone(), ADMIN, &team, "alice", vec![])
+                .unwrap_err();
+            assert!(format!("{:?}", err).contains("No funds sent"));
+
+            mint_sub_path_native(
+                &mut app,
+                paths.clone(),
+                ADMIN,
+                &team,
+                "alice",
+                coins(50, NATIVE_DENOM),
+            )
+            .unwrap();
+
+            let resp = get_nft_owner(&mut app, whoami.clone(), format!("{}::alice", team));
+            assert_eq!(resp.owner, ADMIN.to_string());
+
+            // The payment goes straight to the sub-root owner
+            let user_balance = app.wrap().query_balance(USER, NATIVE_DENOM).unwrap().amount;
+            assert_eq!(user_balance, user_balance_before + Uint128::new(50));
+
+            // The owner can take their path back, closing it to new mints
+            app.execute_contract(
+                Addr::unchecked(USER),
+                paths.clone(),
+                &ExecuteMsg::WithdrawSubRoot {
+                    token_id: team.clone(),
+                },
+                &[],
+            )
+            .unwrap();
+            let resp = get_nft_owner(&mut app, whoami, team.clone());
+            assert_eq!(resp.owner, USER.to_string());
+
+            let err = mint_sub_path_native(
+                &mut app,
+                paths,
+                ADMIN,
+                &team,
+                "bob",
+                coins(50, NATIVE_DENOM),
+            )
+            .unwrap_err();
+            assert!(format!("{:?}", err).contains("is not a registered sub-root"));
+        }
+
+        #[test]
+        fn test_sub_root_gating() {
+            let mut app = mock_app();
+            let cw20 = instantiate_cw20(&mut app);
+            let (whoami, paths, token_id) = setup_test_case_with_name(&mut app, None);
+            let team = format!("{}::team", token_id);
+
+            mint_path_native(&mut app, paths.clone(), USER, "team", vec![]).unwrap();
+            register_sub_root(
+                &mut app,
+                whoami,
+                paths.clone(),
+                USER,
+                &team,
+                None,
+                vec![HolderRule::Cw20Balance {
+                    token_address: cw20.to_string(),
+                    min_balance: Uint128::new(1),
+                }],
+            )
+            .unwrap();
+
+            let err = mint_sub_path_native(&mut app, paths.clone(), OUTSIDER, &team, "a", vec![])
+                .unwrap_err();
+            assert!(format!("{:?}", err).contains("Address does not meet"));
+
+            // The root itself is not gated
+            mint_path_native(&mut app, paths.clone(), OUTSIDER, "b", vec![]).unwrap();
+
+            mint_sub_path_native(&mut app, paths, ADMIN, &team, "a", vec![]).unwrap();
+        }
+
+        #[test]
+        fn test_update_sub_root() {
+            let mut app = mock_app();
+            let (whoami, paths, token_id) = setup_test_case_with_name(&mut app, None);
+            let team = format!("{}::team", token_id);
+
+            mint_path_native(&mut app, paths.clone(), USER, "team", vec![]).unwrap();
+            register_sub_root(&mut app, whoami, paths.clone(), USER, &team, None, vec![]).unwrap();
+
+            let payment_details = PaymentDetails::Native {
+                denom: NATIVE_DENOM.to_string(),
+                amount: Uint128::new(10),
+            };
+            let msg = ExecuteMsg::UpdateSubRoot {
+                token_id: team.clone(),
+                payment_details: Some(payment_details.clone()),
+                gating_rules: vec![],
+            };
+            let err = app
+                .execute_contract(Addr::unchecked(ADMIN), paths.clone(), &msg, &[])
+                .unwrap_err();
+            assert!(format!("{:?}", err).contains("Unauthorized"));
+
+            app.execute_contract(Addr::unchecked(USER), paths.clone(), &msg, &[])
+                .unwrap();
+            let resp = get_sub_root(&mut app, paths, &team);
+            assert_eq!(resp.payment_details, Some(payment_details));
+        }
+
+        #[test]
+        fn test_register_sub_root_not_minted_here() {
+            let mut app = mock_app();
+            let (whoami, paths, _token_id) = setup_test_case_with_name(&mut app, None);
+
+            // Minted directly through whoami rather than this contract
+            mint_name(&mut app, whoami.clone(), ADMIN, "other_name").unwrap();
+
+            let err = register_sub_root(&mut app, whoami, paths, ADMIN, "other_name", None, vec![])
+                .unwrap_err();
+            assert!(format!("{:?}", err).contains("was not minted through this contract"));
+        }
+    }
+
+    mod path_prices {
+        use crate::integration_tests::tests::{
+            get_quote_price, mint_path_native, mock_app, setup_test_case_with_name, ADMIN,
+            INVALID_DENOM, NATIVE_DENOM, USER,
+        };
+        use crate::msg::{
+            ExecuteMsg, PathPattern, PathPrice, PathPricesResponse, PatternPrice, PaymentDetails,
+            QueryMsg,
+        };
+        use cosmwasm_std::{coins, Addr, Uint128};
+        use cw_multi_test::{App, AppResponse, Executor};
+
+        fn native_price(amount: u128) -> PaymentDetails {
+            PaymentDetails::Native {
+                denom: NATIVE_DENOM.to_string(),
+                amount: Uint128::new(amount),
+            }
+        }
+
+        fn update_path_prices(
+            app: &mut App,
+            paths_addr: Addr,
+            sender: &str,
+            set: Vec<PathPrice>,
+            remove: Vec<String>,
+        ) -> anyhow::Result<AppResponse> {
+            let msg = ExecuteMsg::UpdatePathPrices { set, remove };
+            app.execute_contract(Addr::unchecked(sender), paths_addr, &msg, &[])
+        }
+
+        fn update_pattern_prices(
+            app: &mut App,
+            paths_addr: Addr,
+            sender: &str,
+            patterns: Vec<PatternPrice>,
+        ) -> anyhow::Result<AppResponse> {
+            let msg = ExecuteMsg::UpdatePatternPrices { patterns };
+            app.execute_contract(Addr::unchecked(sender), paths_addr, &msg, &[])
+        }
+
+        fn get_path_prices(
+            app: &mut App,
+            paths_addr: Addr,
+            start_after: Option<String>,
+            limit: Option<u32>,
+        ) -> PathPricesResponse {
+            app.wrap()
+                .query_wasm_smart(paths_addr, &QueryMsg::PathPrices { start_after, limit })
+                .unwrap()
+        }
+
+        #[test]
+        fn test_exact_path_price() {
+            let mut app = mock_app();
+            let (_whoami, paths, _token_id) =
+                setup_test_case_with_name(&mut app, Some(native_price(100)));
+
+            update_path_prices(
+                &mut app,
+                paths.clone(),
+                ADMIN,
+                vec![PathPrice {
+                    path: "gm".to_string(),
+                    payment_details: native_price(500),
+                }],
+                vec![],
+            )
+            .unwrap();
+
+            let resp = get_quote_price(&mut app, paths.clone(), USER, "gm");
+            assert_eq!(resp.amount, Uint128::new(500));
+
+            let err = mint_path_native(
+                &mut app,
+                paths.clone(),
+                USER,
+                "gm",
+                coins(100, NATIVE_DENOM),
+            )
+            .unwrap_err();
+            assert!(format!("{:?}", err).contains("Insufficient funds"));
+            mint_path_native(
+                &mut app,
+                paths.clone(),
+                USER,
+                "gm",
+                coins(500, NATIVE_DENOM),
+            )
+            .unwrap();
+
+            // Other paths keep the base price
+            mint_path_native(&mut app, paths.clone(), USER, "a", coins(100, NATIVE_DENOM)).unwrap();
+
+            // Removing the override falls back to the base price
+            update_path_prices(
+                &mut app,
+                paths.clone(),
+                ADMIN,
+                vec![],
+                vec!["gm".to_string()],
+            )
+            .unwrap();
+            let resp = get_quote_price(&mut app, paths, USER, "gm");
+            assert_eq!(resp.amount, Uint128::new(100));
+        }
+
+        #[test]
+        fn test_pattern_price() {
+            let mut app = mock_app();
+            let (_whoami, paths, _token_id) =
+                setup_test_case_with_name(&mut app, Some(native_price(100)));
+
+            update_pattern_prices(
+                &mut app,
+                paths.clone(),
+                ADMIN,
+                vec![
+                    PatternPrice {
+                        pattern: PathPattern::Prefix {
+                            value: "dao".to_string(),
+                        },
+                        payment_details: native_price(300),
+                    },
+                    PatternPrice {
+                        pattern: PathPattern::Suffix {
+                            value: "-nft".to_string(),
+                        },
+                        payment_details: native_price(20),
+                    },
+                    PatternPrice {
+                        pattern: PathPattern::Contains {
+                            value: "x".to_string(),
+                        },
+                        payment_details: native_price(50),
+                    },
+                ],
+            )
+            .unwrap();
+            update_path_prices(
+                &mut app,
+                paths.clone(),
+                ADMIN,
+                vec![PathPrice {
+                    path: "dao".to_string(),
+                    payment_details: native_price(1000),
+                }],
+                vec![],
+            )
+            .unwrap();
+
+            let quote =
+                |app: &mut App, path| get_quote_price(app, paths.clone(), USER, path).amount;
+            // Exact prices win over patterns
+            assert_eq!(quote(&mut app, "dao"), Uint128::new(1000));
+            assert_eq!(quote(&mut app, "daohaus"), Uint128::new(300));
+            assert_eq!(quote(&mut app, "cool-nft"), Uint128::new(20));
+            // The first matching pattern applies
+            assert_eq!(quote(&mut app, "dao-x"), Uint128::new(300));
+            assert_eq!(quote(&mut app, "xyz"), Uint128::new(50));
+            assert_eq!(quote(&mut app, "a"), Uint128::new(100));
+
+            mint_path_native(
+                &mut app,
+                paths.clone(),
+                USER,
+                "cool-nft",
+                coins(20, NATIVE_DENOM),
+            )
+            .unwrap();
+        }
+
+        #[test]
+        fn test_path_price_different_asset() {
+            let mut app = mock_app();
+            let (_whoami, paths, _token_id) =
+                setup_test_case_with_name(&mut app, Some(native_price(100)));
+
+            let err = update_path_prices(
+                &mut app,
+                paths,
+                ADMIN,
+                vec![PathPrice {
+                    path: "gm".to_string(),
+                    payment_details: PaymentDetails::Native {
+                        denom: INVALID_DENOM.to_string(),
+                        amount: Uint128::new(500),
+                    },
+                }],
+                vec![],
+            )
+            .unwrap_err();
+            assert!(format!("{:?}", err).contains("same token as the base price"));
+        }
+
+        #[test]
+        fn test_path_price_free_minting() {
+            let mut app = mock_app();
+            let (_whoami, paths, _token_id) = setup_test_case_with_name(&mut app, None);
+
+            let err = update_pattern_prices(
+                &mut app,
+                paths,
+                ADMIN,
+                vec![PatternPrice {
+                    pattern: PathPattern::Prefix {
+                        value: "a".to_string(),
+                    },
+                    payment_details: native_price(500),
+                }],
+            )
+            .unwrap_err();
+            assert!(format!("{:?}", err).contains("same token as the base price"));
+        }
+
+        #[test]
+        #[should_panic(expected = "Unauthorized")]
+        fn test_update_path_prices_unauthorized() {
+            let mut app = mock_app();
+            let (_whoami, paths, _token_id) =
+                setup_test_case_with_name(&mut app, Some(native_price(100)));
+
+            update_path_prices(
+                &mut app,
+                paths,
+                USER,
+                vec![PathPrice {
+                    path: "gm".to_string(),
+                    payment_details: native_price(1),
+                }],
+                vec![],
+            )
+            .unwrap();
+        }
+
+        #[test]
+        fn test_query_path_prices() {
+            let mut app = mock_app();
+            let (_whoami, paths, _token_id) =
+                setup_test_case_with_name(&mut app, Some(native_price(100)));
+
+            let set = ["nft", "dao", "gm"]
+                .iter()
+                .map(|path| PathPrice {
+                    path: path.to_string(),
+                    payment_details: native_price(500),
+                })
+                .collect();
+            update_path_prices(&mut app, paths.clone(), ADMIN, set, vec![]).unwrap();
+
+            let resp = get_path_prices(&mut app, paths.clone(), None, Some(2));
+            let listed: Vec<_> = resp
+                .prices
+                .iter()
+                .map(|price| price.path.as_str())
+                .collect();
+            assert_eq!(listed, vec!["dao", "gm"]);
+            assert!(resp.patterns.is_empty());
+
+            let resp = get_path_prices(&mut app, paths, Some("gm".to_string()), None);
+            let listed: Vec<_> = resp
+                .prices
+                .iter()
+                .map(|price| price.path.as_str())
+                .collect();
+            assert_eq!(listed, vec!["nft"]);
+        }
+    }
+
+    mod bundles {
+        use crate::integration_tests::tests::{
+            get_nft_owner, get_payment_details_balance, instantiate_cw20, mint_path_native,
+            mock_app, setup_test_case_with_name, ADMIN, NATIVE_DENOM, USER,
+        };
+        use crate::msg::{
+            BundleDiscount, BundleDiscountsResponse, ExecuteMsg, PaymentDetails, QueryMsg,
+            QuotePathsResponse, ReceiveMsg,
+        };
+        use cosmwasm_std::{coins, to_binary, Addr, Coin, Decimal, Uint128};
+        use cw20::Cw20ExecuteMsg;
+        use cw_multi_test::{App, AppResponse, Executor};
+
+        fn update_bundle_discounts(
+            app: &mut App,
+            paths_addr: Addr,
+            sender: &str,
+            bundle_discounts: Vec<BundleDiscount>,
+        ) -> anyhow::Result<AppResponse> {
+            let msg = ExecuteMsg::UpdateBundleDiscounts { bundle_discounts };
+            app.execute_contract(Addr::unchecked(sender), paths_addr, &msg, &[])
+        }
+
+        fn mint_paths_native(
+            app: &mut App,
+            paths_addr: Addr,
+            sender: &str,
+            paths: &[&str],
+            funds: Vec<Coin>,
+        ) -> anyhow::Result<AppResponse> {
+            let msg = ExecuteMsg::MintPaths {
+                paths: paths.iter().map(|path| path.to_string()).collect(),
+                referrer: None,
+                parent: None,
+                max_price: None,
+                allowlist: None,
+            };
+            app.execute_contract(Addr::unchecked(sender), paths_addr, &msg, &funds)
+        }
+
+        fn get_quote_paths(
+            app: &mut App,
+            paths_addr: Addr,
+            address: &str,
+            paths: &[&str],
+        ) -> QuotePathsResponse {
+            app.wrap()
+                .query_wasm_smart(
+                    paths_addr,
+                    &QueryMsg::QuotePaths {
+                        address: address.to_string(),
+                        paths: paths.iter().map(|path| path.to_string()).collect(),
+                    },
+                )
+                .unwrap()
+        }
+
+        fn setup_bundle(app: &mut App) -> (Addr, Addr, String) {
+            let (whoami, paths, token_id) = setup_test_case_with_name(
+                app,
+                Some(PaymentDetails::Native {
+                    denom: NATIVE_DENOM.to_string(),
+                    amount: Uint128::new(100),
+                }),
+            );
+            update_bundle_discounts(
+                app,
+                paths.clone(),
+                ADMIN,
+                vec![
+                    BundleDiscount {
+                        min_quantity: 3,
+                        discount: Decimal::percent(10),
+                    },
+                    BundleDiscount {
+                        min_quantity: 5,
+                        discount: Decimal::percent(20),
+                    },
+                ],
+            )
+            .unwrap();
+            (whoami, paths, token_id)
+        }
+
+        #[test]
+        fn test_mint_bundle() {
+            let mut app = mock_app();
+            let (whoami, paths, token_id) = setup_bundle(&mut app);
+
+            let resp = get_quote_paths(&mut app, paths.clone(), USER, &["a", "b", "c"]);
+            assert_eq!(resp.bundle_discount, Some(Decimal::percent(10)));
+            assert_eq!(resp.quotes.len(), 3);
+            assert_eq!(resp.quotes[0].amount, Uint128::new(90));
+            assert_eq!(resp.amount, Uint128::new(270));
+
+            mint_paths_native(
+                &mut app,
+                paths.clone(),
+                USER,
+                &["a", "b", "c"],
+                coins(270, NATIVE_DENOM),
+            )
+            .unwrap();
+
+            for path in ["a", "b", "c"] {
+                let resp =
+                    get_nft_owner(&mut app, whoami.clone(), format!("{}::{}", token_id, path));
+                assert_eq!(resp.owner, USER.to_string());
+            }
+
+            let resp = get_payment_details_balance(&mut app, paths);
+            assert_eq!(resp.amount, Uint128::new(270));
+        }
+
+        #[test]
+        fn test_largest_tier_applies() {
+            let mut app = mock_app();
+            let (_whoami, paths, _token_id) = setup_bundle(&mut app);
+
+            let resp = get_quote_paths(&mut app, paths.clone(), USER, &["a", "b", "c", "d", "e"]);
+            assert_eq!(resp.bundle_discount, Some(Decimal::percent(20)));
+            assert_eq!(resp.amount, Uint128::new(400));
+
+            let resp = get_quote_paths(&mut app, paths.clone(), USER, &["a", "b"]);
+            assert_eq!(resp.bundle_discount, None);
+            assert_eq!(resp.amount, Uint128::new(200));
+
+            let resp: BundleDiscountsResponse = app
+                .wrap()
+                .query_wasm_smart(paths, &QueryMsg::BundleDiscounts {})
+                .unwrap();
+            assert_eq!(resp.bundle_discounts[0].min_quantity, 5);
+        }
+
+        #[test]
+        #[should_panic(expected = "Insufficient funds sent to mint a path")]
+        fn test_mint_bundle_full_price() {
+            let mut app = mock_app();
+            let (_whoami, paths, _token_id) = setup_bundle(&mut app);
+
+            mint_paths_native(
+                &mut app,
+                paths,
+                USER,
+                &["a", "b", "c"],
+                coins(300, NATIVE_DENOM),
+            )
+            .unwrap();
+        }
+
+        #[test]
+        #[should_panic(expected = "Path a appears more than once")]
+        fn test_mint_bundle_duplicate_path() {
+            let mut app = mock_app();
+            let (_whoami, paths, _token_id) = setup_bundle(&mut app);
+
+            mint_paths_native(
+                &mut app,
+                paths,
+                USER,
+                &["a", "b", "a"],
+                coins(270, NATIVE_DENOM),
+            )
+            .unwrap();
+        }
+
+        #[test]
+        fn test_mint_bundle_with_taken_path() {
+            let mut app = mock_app();
+            let (whoami, paths, token_id) = setup_bundle(&mut app);
+
+            mint_path_native(&mut app, paths.clone(), USER, "b", coins(100, NATIVE_DENOM)).unwrap();
+
+            let err = mint_paths_native(
+                &mut app,
+                paths,
+                ADMIN,
+                &["a", "b", "c"],
+                coins(270, NATIVE_DENOM),
+            )
+            .unwrap_err();
+            assert!(format!("{:?}", err).contains("Path b is already taken"));
+
+            // The whole batch is rolled back
+            let err = app
+                .wrap()
+                .query_wasm_smart::<cw721::OwnerOfResponse>(
+                    whoami,
+                    &cw721::Cw721QueryMsg::OwnerOf {
+                        token_id: format!("{}::a", token_id),
+                        include_expired: None,
+                    },
+                )
+                .unwrap_err();
+            assert!(err.to_string().contains("not found"));
+        }
+
+        #[test]
+        fn test_mint_bundle_cw20() {
+            let mut app = mock_app();
+            let cw20_addr = instantiate_cw20(&mut app);
+            let (whoami, paths, token_id) = setup_test_case_with_name(
+                &mut app,
+                Some(PaymentDetails::Cw20 {
+                    token_address: cw20_addr.to_string(),
+                    amount: Uint128::new(100),
+                }),
+            );
+            update_bundle_discounts(
+                &mut app,
+                paths.clone(),
+                ADMIN,
+                vec![BundleDiscount {
+                    min_quantity: 3,
+                    discount: Decimal::percent(10),
+                }],
+            )
+            .unwrap();
+
+            let msg = Cw20ExecuteMsg::Send {
+                contract: paths.to_string(),
+                amount: Uint128::new(270),
+                msg: to_binary(&ReceiveMsg::MintPaths {
+                    paths: vec!["a".to_string(), "b".to_string(), "c".to_string()],
+                    referrer: None,
+                    parent: None,
+                    max_price: None,
+                    allowlist: None,
+                })
+                .unwrap(),
+            };
+            app.execute_contract(Addr::unchecked(USER), cw20_addr, &msg, &[])
+                .unwrap();
+
+            let resp = get_nft_owner(&mut app, whoami, format!("{}::c", token_id));
+            assert_eq!(resp.owner, USER.to_string());
+        }
+
+        #[test]
+        fn test_update_bundle_discounts_invalid() {
+            let mut app = mock_app();
+            let (_whoami, paths, _token_id) = setup_bundle(&mut app);
+
+            let discount = BundleDiscount {
+                min_quantity: 3,
+                discount: Decimal::percent(10),
+            };
+            let err =
+                update_bundle_discounts(&mut app, paths.clone(), USER, vec![discount]).unwrap_err();
+            assert!(format!("{:?}", err).contains("Unauthorized"));
+
+            let err = update_bundle_discounts(
+                &mut app,
+                paths.clone(),
+                ADMIN,
+                vec![BundleDiscount {
+                    min_quantity: 1,
+                    discount: Decimal::percent(10),
+                }],
+            )
+            .unwrap_err();
+            assert!(format!("{:?}", err).contains("Bundle discounts must be for at least 2 paths"));
+
+            let err = update_bundle_discounts(
+                &mut app,
+                paths,
+                ADMIN,
+                vec![BundleDiscount {
+                    min_quantity: 3,
+                    discount: Decimal::one(),
+                }],
+            )
+            .unwrap_err();
+            assert!(format!("{:?}", err).contains("Discounts must be less than 100%"));
+        }
+    }
+
+    mod max_price {
+        use crate::integration_tests::tests::{
+            event_attribute, get_cw20_balance, get_nft_owner, get_payment_details_balance,
+            instantiate_cw20, mock_app, setup_test_case_with_name, NATIVE_DENOM, USER,
+        };
+        use crate::msg::{ExecuteMsg, PaymentDetails, ReceiveMsg};
+        use cosmwasm_std::{coins, to_binary, Addr, Coin, Uint128};
+        use cw20::Cw20ExecuteMsg;
+        use cw_multi_test::{App, AppResponse, Executor};
+
+        fn mint_path_native_capped(
+            app: &mut App,
+            paths_addr: Addr,
+            sender: &str,
+            path: &str,
+            max_price: u128,
+            payment: Vec<Coin>,
+        ) -> anyhow::Result<AppResponse> {
+            app.execute_contract(
+                Addr::unchecked(sender),
+                paths_addr,
+                &ExecuteMsg::MintPath {
+                    path: path.to_string(),
+                    referrer: None,
+                    parent: None,
+                    max_price: Some(Uint128::new(max_price)),
+                    allowlist: None,
+                },
+                &payment,
+            )
+        }
+
+        fn native_price(amount: u128) -> Option<PaymentDetails> {
+            Some(PaymentDetails::Native {
+                denom: NATIVE_DENOM.to_string(),
+                amount: Uint128::new(amount),
+            })
+        }
+
+        #[test]
+        fn test_surplus_refunded() {
+            let mut app = mock_app();
+            let (whoami, paths, token_id) = setup_test_case_with_name(&mut app, native_price(100));
+            let starting_balance = app.wrap().query_balance(USER, NATIVE_DENOM).unwrap();
+
+            let resp = mint_path_native_capped(
+                &mut app,
+                paths.clone(),
+                USER,
+                "a",
+                150,
+                coins(150, NATIVE_DENOM),
+            )
+            .unwrap();
+            // The contract pays the refund back to the minter
+            assert_eq!(
+                event_attribute(&resp, "wasm-whoami_paths_refund", "payer"),
+                Some(paths.to_string())
+            );
+            assert_eq!(
+                event_attribute(&resp, "wasm-whoami_paths_refund", "recipient"),
+                Some(USER.to_string())
+            );
+
+            let resp = get_nft_owner(&mut app, whoami, format!("{}::a", token_id));
+            assert_eq!(resp.owner, USER.to_string());
+
+            let balance = app.wrap().query_balance(USER, NATIVE_DENOM).unwrap();
+            assert_eq!(starting_balance.amount - balance.amount, Uint128::new(100));
+            let resp = get_payment_details_balance(&mut app, paths);
+            assert_eq!(resp.amount, Uint128::new(100));
+        }
+
+        #[test]
+        #[should_panic(expected = "Price 100 is above the maximum price of 80")]
+        fn test_price_above_max() {
+            let mut app = mock_app();
+            let (_whoami, paths, _token_id) =
+                setup_test_case_with_name(&mut app, native_price(100));
+
+            mint_path_native_capped(&mut app, paths, USER, "a", 80, coins(100, NATIVE_DENOM))
+                .unwrap();
+        }
+
+        #[test]
+        #[should_panic(expected = "Insufficient funds sent to mint a path")]
+        fn test_underpaid_within_max() {
+            let mut app = mock_app();
+            let (_whoami, paths, _token_id) =
+                setup_test_case_with_name(&mut app, native_price(100));
+
+            mint_path_native_capped(&mut app, paths, USER, "a", 150, coins(90, NATIVE_DENOM))
+                .unwrap();
+        }
+
+        #[test]
+        fn test_cw20_surplus_refunded() {
+            let mut app = mock_app();
+            let cw20_addr = instantiate_cw20(&mut app);
+            let (_whoami, paths, _token_id) = setup_test_case_with_name(
+                &mut app,
+                Some(PaymentDetails::Cw20 {
+                    token_address: cw20_addr.to_string(),
+                    amount: Uint128::new(100),
+                }),
+            );
+            let starting_balance = get_cw20_balance(&mut app, cw20_addr.clone(), USER);
+
+            let msg = Cw20ExecuteMsg::Send {
+                contract: paths.to_string(),
+                amount: Uint128::new(120),
+                msg: to_binary(&ReceiveMsg::MintPath {
+                    path: "a".to_string(),
+                    referrer: None,
+                    parent: None,
+                    max_price: Some(Uint128::new(120)),
+                    allowlist: None,
+                })
+                .unwrap(),
+            };
+            app.execute_contract(Addr::unchecked(USER), cw20_addr.clone(), &msg, &[])
+                .unwrap();
+
+            let balance = get_cw20_balance(&mut app, cw20_addr, USER);
+            assert_eq!(
+                starting_balance.balance - balance.balance,
+                Uint128::new(100)
+            );
+        }
+    }
+
+    mod commit_reveal {
+        use crate::integration_tests::tests::{
+            get_cw20_balance, get_nft_owner, instantiate_cw20, mint_path_native, mock_app,
+            setup_test_case_with_name, ADMIN, NATIVE_DENOM, OUTSIDER, USER,
+        };
+        use crate::msg::{
+            CommitWindow, CommitWindowResponse, ExecuteMsg, PaymentDetails, QueryMsg, ReceiveMsg,
+        };
+        use cosmwasm_std::{coins, to_binary, Addr, Binary, Coin, Uint128};
+        use cw20::Cw20ExecuteMsg;
+        use cw_multi_test::{App, AppResponse, Executor};
+        use sha2::{Digest, Sha256};
+
+        const SALT: &str = "pepper";
+
+        fn commitment(path: &str, sender: &str, salt: &str) -> Binary {
+            Binary::from(
+                Sha256::digest(format!("{}:{}:{}", path, sender, salt).as_bytes()).to_vec(),
+            )
+        }
+
+        fn commit_path(
+            app: &mut App,
+            paths_addr: Addr,
+            sender: &str,
+            commitment: Binary,
+        ) -> anyhow::Result<AppResponse> {
+            let msg = ExecuteMsg::CommitPath { commitment };
+            app.execute_contract(Addr::unchecked(sender), paths_addr, &msg, &[])
+        }
+
+        fn reveal_path(
+            app: &mut App,
+            paths_addr: Addr,
+            sender: &str,
+            path: &str,
+            salt: &str,
+            payment: Vec<Coin>,
+        ) -> anyhow::Result<AppResponse> {
+            let msg = ExecuteMsg::RevealPath {
+                path: path.to_string(),
+                salt: salt.to_string(),
+                referrer: None,
+                parent: None,
+                max_price: None,
+                allowlist: None,
+            };
+            app.execute_contract(Addr::unchecked(sender), paths_addr, &msg, &payment)
+        }
+
+        fn skip_blocks(app: &mut App, blocks: u64) {
+            app.update_block(|block| block.height += blocks);
+        }
+
+        fn setup_native(app: &mut App) -> (Addr, Addr, String) {
+            setup_test_case_with_name(
+                app,
+                Some(PaymentDetails::Native {
+                    denom: NATIVE_DENOM.to_string(),
+                    amount: Uint128::new(100),
+                }),
+            )
+        }
+
+        #[test]
+        fn test_commit_and_reveal() {
+            let mut app = mock_app();
+            let (whoami, paths, token_id) = setup_native(&mut app);
+
+            commit_path(&mut app, paths.clone(), USER, commitment("a", USER, SALT)).unwrap();
+            skip_blocks(&mut app, 10);
+            reveal_path(
+                &mut app,
+                paths.clone(),
+                USER,
+                "a",
+                SALT,
+                coins(100, NATIVE_DENOM),
+            )
+            .unwrap();
+
+            let resp = get_nft_owner(&mut app, whoami, format!("{}::a", token_id));
+            assert_eq!(resp.owner, USER.to_string());
+
+            // The commitment is used up by the reveal
+            let err = reveal_path(&mut app, paths, USER, "a", SALT, coins(100, NATIVE_DENOM))
+                .unwrap_err();
+            assert!(format!("{:?}", err).contains("No commitment found"));
+        }
+
+        #[test]
+        #[should_panic(expected = "Commitment cannot be revealed until block")]
+        fn test_reveal_too_early() {
+            let mut app = mock_app();
+            let (_whoami, paths, _token_id) = setup_native(&mut app);
+
+            commit_path(&mut app, paths.clone(), USER, commitment("a", USER, SALT)).unwrap();
+            skip_blocks(&mut app, 1);
+            reveal_path(&mut app, paths, USER, "a", SALT, coins(100, NATIVE_DENOM)).unwrap();
+        }
+
+        #[test]
+        #[should_panic(expected = "Commitment expired at block")]
+        fn test_reveal_expired() {
+            let mut app = mock_app();
+            let (_whoami, paths, _token_id) = setup_native(&mut app);
+
+            commit_path(&mut app, paths.clone(), USER, commitment("a", USER, SALT)).unwrap();
+            skip_blocks(&mut app, 14_401);
+            reveal_path(&mut app, paths, USER, "a", SALT, coins(100, NATIVE_DENOM)).unwrap();
+        }
+
+        #[test]
+        fn test_reveal_wrong_sender_or_salt() {
+            let mut app = mock_app();
+            let (_whoami, paths, _token_id) = setup_native(&mut app);
+
+            commit_path(&mut app, paths.clone(), USER, commitment("a", USER, SALT)).unwrap();
+            skip_blocks(&mut app, 10);
+
+            let err = reveal_path(
+                &mut app,
+                paths.clone(),
+                USER,
+                "a",
+                "salt",
+                coins(100, NATIVE_DENOM),
+            )
+            .unwrap_err();
+            assert!(format!("{:?}", err).contains("No commitment found"));
+
+            let err = reveal_path(&mut app, paths, ADMIN, "a", SALT, coins(100, NATIVE_DENOM))
+                .unwrap_err();
+            assert!(format!("{:?}", err).contains("No commitment found"));
+        }
+
+        #[test]
+        fn test_recommit_by_someone_else() {
+            let mut app = mock_app();
+            let (whoami, paths, token_id) = setup_native(&mut app);
+
+            commit_path(&mut app, paths.clone(), USER, commitment("a", USER, SALT)).unwrap();
+            skip_blocks(&mut app, 10);
+
+            // Replaying the same bytes from another address doesn't move USER's commitment
+            commit_path(
+                &mut app,
+                paths.clone(),
+                OUTSIDER,
+                commitment("a", USER, SALT),
+            )
+            .unwrap();
+            reveal_path(
+                &mut app,
+                paths.clone(),
+                USER,
+                "a",
+                SALT,
+                coins(100, NATIVE_DENOM),
+            )
+            .unwrap();
+
+            let resp = get_nft_owner(&mut app, whoami, format!("{}::a", token_id));
+            assert_eq!(resp.owner, USER.to_string());
+        }
+
+        #[test]
+        fn test_reveal_taken_path() {
+            let mut app = mock_app();
+            let (_whoami, paths, _token_id) = setup_native(&mut app);
+
+            commit_path(&mut app, paths.clone(), USER, commitment("a", USER, SALT)).unwrap();
+            mint_path_native(
+                &mut app,
+                paths.clone(),
+                ADMIN,
+                "a",
+                coins(100, NATIVE_DENOM),
+            )
+            .unwrap();
+            skip_blocks(&mut app, 10);
+
+            let err = reveal_path(&mut app, paths, USER, "a", SALT, coins(100, NATIVE_DENOM))
+                .unwrap_err();
+            assert!(format!("{:?}", err).contains("Path a is already taken"));
+        }
+
+        #[test]
+        fn test_reveal_cw20() {
+            let mut app = mock_app();
+            let cw20_addr = instantiate_cw20(&mut app);
+            let (whoami, paths, token_id) = setup_test_case_with_name(
+                &mut app,
+                Some(PaymentDetails::Cw20 {
+                    token_address: cw20_addr.to_string(),
+                    amount: Uint128::new(100),
+                }),
+            );
+            let starting_balance = get_cw20_balance(&mut app, cw20_addr.clone(), USER);
+
+            commit_path(&mut app, paths.clone(), USER, commitment("a", USER, SALT)).unwrap();
+            skip_blocks(&mut app, 10);
+
+            let msg = Cw20ExecuteMsg::Send {
+                contract: paths.to_string(),
+                amount: Uint128::new(100),
+                msg: to_binary(&ReceiveMsg::RevealPath {
+                    path: "a".to_string(),
+                    salt: SALT.to_string(),
+                    referrer: None,
+                    parent: None,
+                    max_price: None,
+                    allowlist: None,
+                })
+                .unwrap(),
+            };
+            app.execute_contract(Addr::unchecked(USER), cw20_addr.clone(), &msg, &[])
+                .unwrap();
+
+            let resp = get_nft_owner(&mut app, whoami, format!("{}::a", token_id));
+            assert_eq!(resp.owner, USER.to_string());
+            let balance = get_cw20_balance(&mut app, cw20_addr, USER);
+            assert_eq!(
+                starting_balance.balance - balance.balance,
+                Uint128::new(100)
+            );
+        }
+
+        #[test]
+        fn test_update_commit_window() {
+            let mut app = mock_app();
+            let (_whoami, paths, _token_id) = setup_native(&mut app);
+
+            let commit_window = CommitWindow {
+                min_delay: 1,
+                expiry: 5,
+            };
+            let msg = ExecuteMsg::UpdateCommitWindow {
+                commit_window: commit_window.clone(),
+            };
+            let err = app
+                .execute_contract(Addr::unchecked(OUTSIDER), paths.clone(), &msg, &[])
+                .unwrap_err();
+            assert!(format!("{:?}", err).contains("Unauthorized"));
+            app.execute_contract(Addr::unchecked(ADMIN), paths.clone(), &msg, &[])
+                .unwrap();
+
+            let resp: CommitWindowResponse = app
+                .wrap()
+                .query_wasm_smart(paths.clone(), &QueryMsg::CommitWindow {})
+                .unwrap();
+            assert_eq!(resp.commit_window, commit_window);
+
+            commit_path(&mut app, paths.clone(), USER, commitment("a", USER, SALT)).unwrap();
+            skip_blocks(&mut app, 1);
+            reveal_path(
+                &mut app,
+                paths.clone(),
+                USER,
+                "a",
+                SALT,
+                coins(100, NATIVE_DENOM),
+            )
+            .unwrap();
+
+            let msg = ExecuteMsg::UpdateCommitWindow {
+                commit_window: CommitWindow {
+                    min_delay: 5,
+                    expiry: 5,
+                },
+            };
+            let err = app
+                .execute_contract(Addr::unchecked(ADMIN), paths, &msg, &[])
+                .unwrap_err();
+            assert!(
+                format!("{:?}", err).contains("The reveal delay must be shorter than the expiry")
+            );
+        }
+
+        #[test]
+        fn test_require_commitment() {
+            let mut app = mock_app();
+            let (whoami, paths, token_id) = setup_native(&mut app);
+
+            let msg = ExecuteMsg::UpdateRequireCommitment {
+                require_commitment: true,
+            };
+            let err = app
+                .execute_contract(Addr::unchecked(OUTSIDER), paths.clone(), &msg, &[])
+                .unwrap_err();
+            assert!(format!("{:?}", err).contains("Unauthorized"));
+            app.execute_contract(Addr::unchecked(ADMIN), paths.clone(), &msg, &[])
+                .unwrap();
+
+            let resp: CommitWindowResponse = app
+                .wrap()
+                .query_wasm_smart(paths.clone(), &QueryMsg::CommitWindow {})
+                .unwrap();
+            assert!(resp.require_commitment);
+
+            commit_path(&mut app, paths.clone(), USER, commitment("a", USER, SALT)).unwrap();
+            skip_blocks(&mut app, 10);
+
+            // Someone copying the path out of USER's pending reveal can't mint it directly
+            let err = mint_path_native(
+                &mut app,
+                paths.clone(),
+                OUTSIDER,
+                "a",
+                coins(100, NATIVE_DENOM),
+            )
+            .unwrap_err();
+            assert!(format!("{:?}", err).contains("Paths can only be minted by committing"));
+
+            // nor replay the reveal itself
+            let err = reveal_path(
+                &mut app,
+                paths.clone(),
+                OUTSIDER,
+                "a",
+                SALT,
+                coins(100, NATIVE_DENOM),
+            )
+            .unwrap_err();
+            assert!(format!("{:?}", err).contains("No commitment found"));
+
+            reveal_path(&mut app, paths, USER, "a", SALT, coins(100, NATIVE_DENOM)).unwrap();
+            let resp = get_nft_owner(&mut app, whoami, format!("{}::a", token_id));
+            assert_eq!(resp.owner, USER.to_string());
+        }
+
+        #[test]
+        fn test_require_commitment_cw20() {
+            let mut app = mock_app();
+            let cw20_addr = instantiate_cw20(&mut app);
+            let (_whoami, paths, _token_id) = setup_test_case_with_name(
+                &mut app,
+                Some(PaymentDetails::Cw20 {
+                    token_address: cw20_addr.to_string(),
+                    amount: Uint128::new(100),
+                }),
+            );
+            let msg = ExecuteMsg::UpdateRequireCommitment {
+                require_commitment: true,
+            };
+            app.execute_contract(Addr::unchecked(ADMIN), paths.clone(), &msg, &[])
+                .unwrap();
+
+            let msg = Cw20ExecuteMsg::Send {
+                contract: paths.to_string(),
+                amount: Uint128::new(100),
+                msg: to_binary(&ReceiveMsg::MintPath {
+                    path: "a".to_string(),
+                    referrer: None,
+                    parent: None,
+                    max_price: None,
+                    allowlist: None,
+                })
+                .unwrap(),
+            };
+            let err = app
+                .execute_contract(Addr::unchecked(OUTSIDER), cw20_addr, &msg, &[])
+                .unwrap_err();
+            assert!(format!("{:?}", err).contains("Paths can only be minted by committing"));
+        }
+
+        #[test]
+        fn test_commit_window_no_overflow() {
+            let mut app = mock_app();
+            let (whoami, paths, token_id) = setup_native(&mut app);
+
+            let msg = ExecuteMsg::UpdateCommitWindow {
+                commit_window: CommitWindow {
+                    min_delay: 1,
+                    expiry: u64::MAX,
+                },
+            };
+            app.execute_contract(Addr::unchecked(ADMIN), paths.clone(), &msg, &[])
+                .unwrap();
+
+            commit_path(&mut app, paths.clone(), USER, commitment("a", USER, SALT)).unwrap();
+            skip_blocks(&mut app, 1);
+            reveal_path(
+                &mut app,
+                paths.clone(),
+                USER,
+                "a",
+                SALT,
+                coins(100, NATIVE_DENOM),
+            )
+            .unwrap();
+
+            let resp = get_nft_owner(&mut app, whoami, format!("{}::a", token_id));
+            assert_eq!(resp.owner, USER.to_string());
+        }
+
+        #[test]
+        #[should_panic(expected = "Commitments must be a 32 byte sha256 hash")]
+        fn test_invalid_commitment() {
+            let mut app = mock_app();
+            let (_whoami, paths, _token_id) = setup_native(&mut app);
+
+            commit_path(&mut app, paths, USER, Binary::from(b"a".to_vec())).unwrap();
+        }
+    }
+
+    mod vouchers {
+        use crate::integration_tests::tests::{
+            get_nft_owner, get_payment_details_balance, mock_app, setup_test_case_with_name, ADMIN,
+            NATIVE_DENOM, OUTSIDER, USER,
+        };
+        use crate::msg::{ExecuteMsg, PaymentDetails, QueryMsg, Voucher, VoucherSignerResponse};
+        use cosmwasm_std::{coins, to_binary, Addr, Binary, Coin, Uint128};
+        use cw_multi_test::{App, AppResponse, Executor};
+        use cw_utils::Expiration;
+        use k256::ecdsa::signature::Signer;
+        use k256::ecdsa::{Signature, SigningKey};
+
+        fn signing_key() -> SigningKey {
+            SigningKey::from_bytes(&[7; 32]).unwrap()
+        }
+
+        fn sign(voucher: &Voucher) -> Binary {
+            let signature: Signature = signing_key().sign(to_binary(voucher).unwrap().as_slice());
+            Binary::from(signature.as_ref().to_vec())
+        }
+
+        fn voucher(
+            app: &App,
+            paths_addr: &Addr,
+            recipient: &str,
+            path: Option<&str>,
+            price: u128,
+            nonce: u64,
+        ) -> Voucher {
+            Voucher {
+                contract_address: paths_addr.to_string(),
+                chain_id: app.block_info().chain_id,
+                recipient: recipient.to_string(),
+                path: path.map(|path| path.to_string()),
+                price: Uint128::new(price),
+                expiry: Expiration::Never {},
+                nonce,
+            }
+        }
+
+        fn update_voucher_signer(
+            app: &mut App,
+            paths_addr: Addr,
+            sender: &str,
+            pubkey: Option<Binary>,
+        ) -> anyhow::Result<AppResponse> {
+            let msg = ExecuteMsg::UpdateVoucherSigner { pubkey };
+            app.execute_contract(Addr::unchecked(sender), paths_addr, &msg, &[])
+        }
+
+        fn mint_with_voucher(
+            app: &mut App,
+            paths_addr: Addr,
+            sender: &str,
+            path: &str,
+            voucher: Voucher,
+            signature: Binary,
+            payment: Vec<Coin>,
+        ) -> anyhow::Result<AppResponse> {
+            let msg = ExecuteMsg::MintWithVoucher {
+                path: path.to_string(),
+                voucher,
+                signature,
+            };
+            app.execute_contract(Addr::unchecked(sender), paths_addr, &msg, &payment)
+        }
+
+        fn setup_vouchers(app: &mut App) -> (Addr, Addr, String) {
+            let (whoami, paths, token_id) = setup_test_case_with_name(
+                app,
+                Some(PaymentDetails::Native {
+                    denom: NATIVE_DENOM.to_string(),
+                    amount: Uint128::new(100),
+                }),
+            );
+            let pubkey = Binary::from(signing_key().verifying_key().to_bytes().to_vec());
+            update_voucher_signer(app, paths.clone(), ADMIN, Some(pubkey)).unwrap();
+            (whoami, paths, token_id)
+        }
+
+        #[test]
+        fn test_free_voucher() {
+            let mut app = mock_app();
+            let (whoami, paths, token_id) = setup_vouchers(&mut app);
+
+            let voucher = voucher(&app, &paths, USER, None, 0, 1);
+            let signature = sign(&voucher);
+            mint_with_voucher(
+                &mut app,
+                paths.clone(),
+                USER,
+                "a",
+                voucher.clone(),
+                signature.clone(),
+                vec![],
+            )
+            .unwrap();
+
+            let resp = get_nft_owner(&mut app, whoami, format!("{}::a", token_id));
+            assert_eq!(resp.owner, USER.to_string());
+
+            let err = mint_with_voucher(&mut app, paths, USER, "b", voucher, signature, vec![])
+                .unwrap_err();
+            assert!(format!("{:?}", err).contains("Voucher 1 has already been redeemed"));
+        }
+
+        #[test]
+        fn test_discounted_voucher() {
+            let mut app = mock_app();
+            let (whoami, paths, token_id) = setup_vouchers(&mut app);
+
+            let voucher = voucher(&app, &paths, USER, Some("a"), 30, 1);
+            let signature = sign(&voucher);
+            mint_with_voucher(
+                &mut app,
+                paths.clone(),
+                USER,
+                "a",
+                voucher,
+                signature,
+                coins(30, NATIVE_DENOM),
+            )
+            .unwrap();
+
+            let resp = get_nft_owner(&mut app, whoami, format!("{}::a", token_id));
+            assert_eq!(resp.owner, USER.to_string());
+            let resp = get_payment_details_balance(&mut app, paths);
+            assert_eq!(resp.amount, Uint128::new(30));
+        }
+
+        #[test]
+        fn test_invalid_vouchers() {
+            let mut app = mock_app();
+            let (_whoami, paths, _token_id) = setup_vouchers(&mut app);
+
+            let valid = voucher(&app, &paths, USER, Some("a"), 0, 1);
+            let signature = sign(&valid);
+
+            let err = mint_with_voucher(
+                &mut app,
+                paths.clone(),
+                OUTSIDER,
+                "a",
+                valid.clone(),
+                signature.clone(),
+                vec![],
+            )
+            .unwrap_err();
+            assert!(format!("{:?}", err).contains("Voucher was issued to a different address"));
+
+            let err = mint_with_voucher(
+                &mut app,
+                paths.clone(),
+                USER,
+                "b",
+                valid.clone(),
+                signature.clone(),
+                vec![],
+            )
+            .unwrap_err();
+            assert!(format!("{:?}", err).contains("Voucher is not valid for path b"));
+
+            // Changing any field breaks the signature
+            let mut tampered = valid;
+            tampered.path = None;
+            let err = mint_with_voucher(
+                &mut app,
+                paths.clone(),
+                USER,
+                "b",
+                tampered,
+                signature,
+                vec![],
+            )
+            .unwrap_err();
+            assert!(format!("{:?}", err).contains("Voucher signature is not valid"));
+
+            let mut expired = voucher(&app, &paths, USER, Some("a"), 0, 2);
+            expired.expiry = Expiration::AtHeight(app.block_info().height);
+            let signature = sign(&expired);
+            let err = mint_with_voucher(&mut app, paths, USER, "a", expired, signature, vec![])
+                .unwrap_err();
+            assert!(format!("{:?}", err).contains("Voucher has expired"));
+        }
+
+        #[test]
+        fn test_voucher_for_another_contract_or_chain() {
+            let mut app = mock_app();
+            let (whoami, paths, _token_id) = setup_vouchers(&mut app);
+
+            let mut other_contract = voucher(&app, &paths, USER, Some("a"), 0, 1);
+            other_contract.contract_address = whoami.to_string();
+            let signature = sign(&other_contract);
+            let err = mint_with_voucher(
+                &mut app,
+                paths.clone(),
+                USER,
+                "a",
+                other_contract,
+                signature,
+                vec![],
+            )
+            .unwrap_err();
+            assert!(format!("{:?}", err)
+                .contains("Voucher was issued for a different contract or chain"));
+
+            let mut other_chain = voucher(&app, &paths, USER, Some("a"), 0, 1);
+            other_chain.chain_id = "another-chain-1".to_string();
+            let signature = sign(&other_chain);
+            let err = mint_with_voucher(&mut app, paths, USER, "a", other_chain, signature, vec![])
+                .unwrap_err();
+            assert!(format!("{:?}", err)
+                .contains("Voucher was issued for a different contract or chain"));
+        }
+
+        #[test]
+        fn test_vouchers_disabled() {
+            let mut app = mock_app();
+            let (_whoami, paths, _token_id) = setup_vouchers(&mut app);
+
+            update_voucher_signer(&mut app, paths.clone(), ADMIN, None).unwrap();
+            let resp: VoucherSignerResponse = app
+                .wrap()
+                .query_wasm_smart(paths.clone(), &QueryMsg::VoucherSigner {})
+                .unwrap();
+            assert_eq!(resp.pubkey, None);
+
+            let voucher = voucher(&app, &paths, USER, None, 0, 1);
+            let signature = sign(&voucher);
+            let err = mint_with_voucher(&mut app, paths, USER, "a", voucher, signature, vec![])
+                .unwrap_err();
+            assert!(format!("{:?}", err).contains("Vouchers are not enabled"));
+        }
+
+        #[test]
+        fn test_update_voucher_signer() {
+            let mut app = mock_app();
+            let (_whoami, paths, _token_id) = setup_vouchers(&mut app);
+
+            let pubkey = Binary::from(signing_key().verifying_key().to_bytes().to_vec());
+            let err =
+                update_voucher_signer(&mut app, paths.clone(), USER, Some(pubkey)).unwrap_err();
+            assert!(format!("{:?}", err).contains("Unauthorized"));
+
+            let err =
+                update_voucher_signer(&mut app, paths, ADMIN, Some(Binary::from(vec![1; 20])))
+                    .unwrap_err();
+            assert!(format!("{:?}", err).contains("Voucher signer must be"));
+        }
+    }
+
+    mod allowlist {
+        use crate::integration_tests::tests::{
+            get_nft_owner, instantiate_cw20, mint_path_native, mock_app, setup_test_case_with_name,
+            ADMIN, NATIVE_DENOM, OUTSIDER, USER,
+        };
+        use crate::merkle::{hash_pair, leaf_hash, verify_proof};
+        use crate::msg::{
+            AllowlistProof, AllowlistUsageResponse, ExecuteMsg, PaymentDetails, QueryMsg,
+            ReceiveMsg,
+        };
+        use cosmwasm_std::{coins, to_binary, Addr, Binary, Coin, Uint128};
+        use cw20::Cw20ExecuteMsg;
+        use cw_multi_test::{App, AppResponse, Executor};
+
+        // USER can mint 2 paths, ADMIN 1, in the tree ((USER, ADMIN), addr5)
+        fn allowlist_root() -> Binary {
+            let users = hash_pair(&leaf_hash(USER, 2), &leaf_hash(ADMIN, 1));
+            Binary::from(hash_pair(&users, &leaf_hash("addr5", 3)))
+        }
+
+        fn user_proof() -> Vec<Binary> {
+            vec![
+                Binary::from(leaf_hash(ADMIN, 1)),
+                Binary::from(leaf_hash("addr5", 3)),
+            ]
+        }
+
+        fn admin_proof() -> Vec<Binary> {
+            vec![
+                Binary::from(leaf_hash(USER, 2)),
+                Binary::from(leaf_hash("addr5", 3)),
+            ]
+        }
+
+        fn update_allowlist_root(
+            app: &mut App,
+            paths_addr: Addr,
+            sender: &str,
+            root: Option<Binary>,
+        ) -> anyhow::Result<AppResponse> {
+            let msg = ExecuteMsg::UpdateAllowlistRoot { root };
+            app.execute_contract(Addr::unchecked(sender), paths_addr, &msg, &[])
+        }
+
+        fn mint_path_allowlisted(
+            app: &mut App,
+            paths_addr: Addr,
+            sender: &str,
+            path: &str,
+            allowlist: Option<AllowlistProof>,
+            payment: Vec<Coin>,
+        ) -> anyhow::Result<AppResponse> {
+            let msg = ExecuteMsg::MintPath {
+                path: path.to_string(),
+                referrer: None,
+                parent: None,
+                max_price: None,
+                allowlist,
+            };
+            app.execute_contract(Addr::unchecked(sender), paths_addr, &msg, &payment)
+        }
+
+        fn setup_allowlist(app: &mut App) -> (Addr, Addr, String) {
+            let (whoami, paths, token_id) = setup_test_case_with_name(
+                app,
+                Some(PaymentDetails::Native {
+                    denom: NATIVE_DENOM.to_string(),
+                    amount: Uint128::new(100),
+                }),
+            );
+            update_allowlist_root(app, paths.clone(), ADMIN, Some(allowlist_root())).unwrap();
+            (whoami, paths, token_id)
+        }
+
+        #[test]
+        fn test_proofs_verify() {
+            let root = allowlist_root();
+            assert!(verify_proof(&root, &leaf_hash(USER, 2), &user_proof()));
+            assert!(verify_proof(&root, &leaf_hash(ADMIN, 1), &admin_proof()));
+            let users = hash_pair(&leaf_hash(USER, 2), &leaf_hash(ADMIN, 1));
+            assert!(verify_proof(
+                &root,
+                &leaf_hash("addr5", 3),
+                &[Binary::from(users)]
+            ));
+
+            assert!(!verify_proof(&root, &leaf_hash(USER, 3), &user_proof()));
+            assert!(!verify_proof(&root, &leaf_hash(OUTSIDER, 2), &user_proof()));
+        }
+
+        #[test]
+        fn test_mint_within_quota() {
+            let mut app = mock_app();
+            let (whoami, paths, token_id) = setup_allowlist(&mut app);
+            let allowlist = AllowlistProof {
+                quota: 2,
+                proof: user_proof(),
+            };
+
+            for path in ["a", "b"] {
+                mint_path_allowlisted(
+                    &mut app,
+                    paths.clone(),
+                    USER,
+                    path,
+                    Some(allowlist.clone()),
+                    coins(100, NATIVE_DENOM),
+                )
+                .unwrap();
+            }
+            let resp = get_nft_owner(&mut app, whoami, format!("{}::b", token_id));
+            assert_eq!(resp.owner, USER.to_string());
+
+            let resp: AllowlistUsageResponse = app
+                .wrap()
+                .query_wasm_smart(
+                    paths.clone(),
+                    &QueryMsg::AllowlistUsage {
+                        address: USER.to_string(),
+                        quota: 2,
+                    },
+                )
+                .unwrap();
+            assert_eq!(resp.used, 2);
+            assert_eq!(resp.remaining, 0);
+
+            let err = mint_path_allowlisted(
+                &mut app,
+                paths,
+                USER,
+                "c",
+                Some(allowlist),
+                coins(100, NATIVE_DENOM),
+            )
+            .unwrap_err();
+            assert!(format!("{:?}", err).contains("Allowlist quota of 2 paths has been used up"));
+        }
+
+        #[test]
+        fn test_mint_without_valid_proof() {
+            let mut app = mock_app();
+            let (_whoami, paths, _token_id) = setup_allowlist(&mut app);
+
+            let err =
+                mint_path_native(&mut app, paths.clone(), USER, "a", coins(100, NATIVE_DENOM))
+                    .unwrap_err();
+            assert!(format!("{:?}", err).contains("Address is not on the allowlist"));
+
+            // Claiming a bigger quota than the leaf was built with
+            let err = mint_path_allowlisted(
+                &mut app,
+                paths.clone(),
+                USER,
+                "a",
+                Some(AllowlistProof {
+                    quota: 3,
+                    proof: user_proof(),
+                }),
+                coins(100, NATIVE_DENOM),
+            )
+            .unwrap_err();
+            assert!(format!("{:?}", err).contains("Address is not on the allowlist"));
+
+            // Using someone else's proof
+            let err = mint_path_allowlisted(
+                &mut app,
+                paths,
+                OUTSIDER,
+                "a",
+                Some(AllowlistProof {
+                    quota: 2,
+                    proof: user_proof(),
+                }),
+                vec![],
+            )
+            .unwrap_err();
+            assert!(format!("{:?}", err).contains("Address is not on the allowlist"));
+        }
+
+        #[test]
+        fn test_clear_allowlist_root() {
+            let mut app = mock_app();
+            let (_whoami, paths, _token_id) = setup_allowlist(&mut app);
+
+            let err = update_allowlist_root(&mut app, paths.clone(), USER, None).unwrap_err();
+            assert!(format!("{:?}", err).contains("Unauthorized"));
+            let err = update_allowlist_root(
+                &mut app,
+                paths.clone(),
+                ADMIN,
+                Some(Binary::from(vec![1; 20])),
+            )
+            .unwrap_err();
+            assert!(format!("{:?}", err).contains("Allowlist root must be a 32 byte sha256 hash"));
+
+            update_allowlist_root(&mut app, paths.clone(), ADMIN, None).unwrap();
+            mint_path_native(&mut app, paths, USER, "a", coins(100, NATIVE_DENOM)).unwrap();
+        }
+
+        #[test]
+        fn test_mint_cw20_with_proof() {
+            let mut app = mock_app();
+            let cw20_addr = instantiate_cw20(&mut app);
+            let (whoami, paths, token_id) = setup_test_case_with_name(
+                &mut app,
+                Some(PaymentDetails::Cw20 {
+                    token_address: cw20_addr.to_string(),
+                    amount: Uint128::new(100),
+                }),
+            );
+            update_allowlist_root(&mut app, paths.clone(), ADMIN, Some(allowlist_root())).unwrap();
+
+            let msg = Cw20ExecuteMsg::Send {
+                contract: paths.to_string(),
+                amount: Uint128::new(100),
+                msg: to_binary(&ReceiveMsg::MintPath {
+                    path: "a".to_string(),
+                    referrer: None,
+                    parent: None,
+                    max_price: None,
+                    allowlist: Some(AllowlistProof {
+                        quota: 1,
+                        proof: admin_proof(),
+                    }),
+                })
+                .unwrap(),
+            };
+            app.execute_contract(Addr::unchecked(ADMIN), cw20_addr, &msg, &[])
+                .unwrap();
+
+            let resp = get_nft_owner(&mut app, whoami, format!("{}::a", token_id));
+            assert_eq!(resp.owner, ADMIN.to_string());
+        }
+    }
+
+    mod cw20_allowance {
+        use crate::integration_tests::tests::{
+            get_cw20_balance, get_nft_owner, get_payment_details_balance, instantiate_cw20,
+            mint_path_native, mock_app, setup_test_case_with_name, USER,
+        };
+        use crate::msg::PaymentDetails;
+        use cosmwasm_std::{Addr, Uint128};
+        use cw20::Cw20ExecuteMsg;
+        use cw_multi_test::{App, Executor};
+
+        fn increase_allowance(app: &mut App, cw20_addr: Addr, paths_addr: Addr, amount: u128) {
+            let msg = Cw20ExecuteMsg::IncreaseAllowance {
+                spender: paths_addr.to_string(),
+                amount: Uint128::new(amount),
+                expires: None,
+            };
+            app.execute_contract(Addr::unchecked(USER), cw20_addr, &msg, &[])
+                .unwrap();
+        }
+
+        fn setup_cw20(app: &mut App) -> (Addr, Addr, Addr, String) {
+            let cw20_addr = instantiate_cw20(app);
+            let (whoami, paths, token_id) = setup_test_case_with_name(
+                app,
+                Some(PaymentDetails::Cw20 {
+                    token_address: cw20_addr.to_string(),
+                    amount: Uint128::new(100),
+                }),
+            );
+            (cw20_addr, whoami, paths, token_id)
+        }
+
+        #[test]
+        fn test_mint_path_with_allowance() {
+            let mut app = mock_app();
+            let (cw20_addr, whoami, paths, token_id) = setup_cw20(&mut app);
+            let starting_balance = get_cw20_balance(&mut app, cw20_addr.clone(), USER);
+
+            increase_allowance(&mut app, cw20_addr.clone(), paths.clone(), 150);
+            mint_path_native(&mut app, paths.clone(), USER, "a", vec![]).unwrap();
+
+            let resp = get_nft_owner(&mut app, whoami, format!("{}::a", token_id));
+            assert_eq!(resp.owner, USER.to_string());
+
+            // Only the price is taken, not the whole allowance
+            let balance = get_cw20_balance(&mut app, cw20_addr, USER);
+            assert_eq!(
+                starting_balance.balance - balance.balance,
+                Uint128::new(100)
+            );
+            let resp = get_payment_details_balance(&mut app, paths);
+            assert_eq!(resp.amount, Uint128::new(100));
+        }
+
+        #[test]
+        #[should_panic(expected = "Allowance of 50 is less than the 100 needed to mint")]
+        fn test_mint_path_allowance_too_small() {
+            let mut app = mock_app();
+            let (cw20_addr, _whoami, paths, _token_id) = setup_cw20(&mut app);
+
+            increase_allowance(&mut app, cw20_addr, paths.clone(), 50);
+            mint_path_native(&mut app, paths, USER, "a", vec![]).unwrap();
+        }
+
+        #[test]
+        #[should_panic(expected = "Allowance of 0 is less than the 100 needed to mint")]
+        fn test_mint_path_no_allowance() {
+            let mut app = mock_app();
+            let (_cw20_addr, _whoami, paths, _token_id) = setup_cw20(&mut app);
+
+            mint_path_native(&mut app, paths, USER, "a", vec![]).unwrap();
+        }
+    }
+
+    mod usd_pricing {
+        use crate::integration_tests::tests::{
+            get_info, get_payment_details_balance, get_quote_price, mint_path_native, mock_app,
+            setup_test_case_with_name, ADMIN, NATIVE_DENOM, USER,
+        };
+        use crate::msg::{
+            ExecuteMsg, OraclePriceResponse, OracleQueryMsg, PathPrice, PaymentDetails, UsdPricing,
+        };
+        use cosmwasm_std::{
+            coins, to_binary, Addr, Binary, Decimal, Deps, DepsMut, Empty, Env, MessageInfo,
+            Response, StdResult, Uint128,
+        };
+        use cw_multi_test::{App, AppResponse, ContractWrapper, Executor};
+        use cw_storage_plus::Item;
+        use std::str::FromStr;
+
+        const ORACLE_PRICE: Item<OraclePriceResponse> = Item::new("oracle_price");
+
+        // Stands in for a price oracle, the price is timestamped when it is set
+        fn execute_oracle(
+            deps: DepsMut,
+            env: Env,
+            _info: MessageInfo,
+            price: Decimal,
+        ) -> StdResult<Response> {
+            ORACLE_PRICE.save(
+                deps.storage,
+                &OraclePriceResponse {
+                    price,
+                    updated_at: env.block.time,
+                },
+            )?;
+            Ok(Response::new())
+        }
+
+        fn instantiate_oracle(
+            _deps: DepsMut,
+            _env: Env,
+            _info: MessageInfo,
+            _msg: Empty,
+        ) -> StdResult<Response> {
+            Ok(Response::new())
+        }
+
+        fn query_oracle(deps: Deps, _env: Env, _msg: OracleQueryMsg) -> StdResult<Binary> {
+            to_binary(&ORACLE_PRICE.load(deps.storage)?)
+        }
+
+        fn set_oracle_price(app: &mut App, oracle: Addr, price: &str) {
+            app.execute_contract(
+                Addr::unchecked(ADMIN),
+                oracle,
+                &Decimal::from_str(price).unwrap(),
+                &[],
+            )
+            .unwrap();
+        }
+
+        fn update_usd_pricing(
+            app: &mut App,
+            paths_addr: Addr,
+            sender: &str,
+            usd_pricing: Option<UsdPricing>,
+        ) -> anyhow::Result<AppResponse> {
+            let msg = ExecuteMsg::UpdateUsdPricing { usd_pricing };
+            app.execute_contract(Addr::unchecked(sender), paths_addr, &msg, &[])
+        }
+
+        fn usd_pricing(oracle: &Addr) -> UsdPricing {
+            UsdPricing {
+                usd_price: Decimal::from_str("5").unwrap(),
+                oracle: oracle.to_string(),
+                tolerance: Decimal::percent(1),
+                max_staleness: 60,
+            }
+        }
+
+        // $5 a path with junox at $2, so 2.5 junox
+        fn setup_usd_pricing(app: &mut App) -> (Addr, Addr) {
+            let (_whoami, paths, _token_id) = setup_test_case_with_name(
+                app,
+                Some(PaymentDetails::Native {
+                    denom: NATIVE_DENOM.to_string(),
+                    amount: Uint128::new(100),
+                }),
+            );
+            let code_id = app.store_code(Box::new(ContractWrapper::new(
+                execute_oracle,
+                instantiate_oracle,
+                query_oracle,
+            )));
+            let oracle = app
+                .instantiate_contract(
+                    code_id,
+                    Addr::unchecked(ADMIN),
+                    &Empty {},
+                    &[],
+                    "oracle",
+                    None,
+                )
+                .unwrap();
+            set_oracle_price(app, oracle.clone(), "0.000002");
+            update_usd_pricing(app, paths.clone(), ADMIN, Some(usd_pricing(&oracle))).unwrap();
+            (paths, oracle)
+        }
+
+        #[test]
+        fn test_mint_path_usd_price() {
+            let mut app = mock_app();
+            let (paths, oracle) = setup_usd_pricing(&mut app);
+
+            let resp = get_quote_price(&mut app, paths.clone(), USER, "a");
+            assert_eq!(resp.amount, Uint128::new(2_500_000));
+            assert!(resp.usd_priced);
+            let resp = get_info(&mut app, paths.clone());
+            assert_eq!(
+                resp.current_price,
+                Some(PaymentDetails::Native {
+                    denom: NATIVE_DENOM.to_string(),
+                    amount: Uint128::new(2_500_000),
+                })
+            );
+
+            mint_path_native(
+                &mut app,
+                paths.clone(),
+                USER,
+                "a",
+                coins(2_500_000, NATIVE_DENOM),
+            )
+            .unwrap();
+            let resp = get_payment_details_balance(&mut app, paths.clone());
+            assert_eq!(resp.amount, Uint128::new(2_500_000));
+
+            // Rounded up rather than undercharging
+            set_oracle_price(&mut app, oracle, "0.000003");
+            let resp = get_quote_price(&mut app, paths, USER, "a");
+            assert_eq!(resp.amount, Uint128::new(1_666_667));
+        }
+
+        #[test]
+        fn test_mint_path_within_tolerance() {
+            let mut app = mock_app();
+            let (paths, _oracle) = setup_usd_pricing(&mut app);
+
+            mint_path_native(
+                &mut app,
+                paths.clone(),
+                USER,
+                "a",
+                coins(2_480_000, NATIVE_DENOM),
+            )
+            .unwrap();
+            let resp = get_payment_details_balance(&mut app, paths.clone());
+            assert_eq!(resp.amount, Uint128::new(2_480_000));
+
+            let err = mint_path_native(&mut app, paths, USER, "b", coins(2_400_000, NATIVE_DENOM))
+                .unwrap_err();
+            assert!(format!("{:?}", err).contains("Insufficient funds sent to mint a path"));
+        }
+
+        #[test]
+        fn test_no_tolerance_for_exact_prices() {
+            let mut app = mock_app();
+            let (paths, _oracle) = setup_usd_pricing(&mut app);
+
+            let msg = ExecuteMsg::UpdatePathPrices {
+                set: vec![PathPrice {
+                    path: "a".to_string(),
+                    payment_details: PaymentDetails::Native {
+                        denom: NATIVE_DENOM.to_string(),
+                        amount: Uint128::new(1_000_000),
+                    },
+                }],
+                remove: vec![],
+            };
+            app.execute_contract(Addr::unchecked(ADMIN), paths.clone(), &msg, &[])
+                .unwrap();
+            let resp = get_quote_price(&mut app, paths.clone(), USER, "a");
+            assert!(!resp.usd_priced);
+
+            // Within the USD tolerance, but an exact price doesn't move with the oracle
+            let err = mint_path_native(
+                &mut app,
+                paths.clone(),
+                USER,
+                "a",
+                coins(995_000, NATIVE_DENOM),
+            )
+            .unwrap_err();
+            assert!(format!("{:?}", err).contains("Insufficient funds sent to mint a path"));
+
+            mint_path_native(&mut app, paths, USER, "a", coins(1_000_000, NATIVE_DENOM)).unwrap();
+        }
+
+        #[test]
+        #[should_panic(expected = "Oracle price for ujunox is stale")]
+        fn test_mint_path_stale_price() {
+            let mut app = mock_app();
+            let (paths, _oracle) = setup_usd_pricing(&mut app);
+
+            app.update_block(|block| block.time = block.time.plus_seconds(61));
+            mint_path_native(&mut app, paths, USER, "a", coins(2_500_000, NATIVE_DENOM)).unwrap();
+        }
+
+        #[test]
+        fn test_update_usd_pricing() {
+            let mut app = mock_app();
+            let (paths, oracle) = setup_usd_pricing(&mut app);
+
+            let err = update_usd_pricing(&mut app, paths.clone(), USER, None).unwrap_err();
+            assert!(format!("{:?}", err).contains("Unauthorized"));
+
+            let mut invalid = usd_pricing(&oracle);
+            invalid.tolerance = Decimal::one();
+            let err =
+                update_usd_pricing(&mut app, paths.clone(), ADMIN, Some(invalid)).unwrap_err();
+            assert!(format!("{:?}", err).contains("Tolerance must be less than 100%"));
+
+            // Back to the configured amount
+            update_usd_pricing(&mut app, paths.clone(), ADMIN, None).unwrap();
+            let resp = get_quote_price(&mut app, paths, USER, "a");
+            assert_eq!(resp.amount, Uint128::new(100));
+        }
+    }
+
+    mod marketplace {
+        use crate::integration_tests::tests::{
+            event_attribute, get_cw20_balance, get_nft_owner, instantiate_cw20, mint_name,
+            mint_path_cw20, mint_path_native, mock_app, setup_test_case_with_name, ADMIN,
+            NATIVE_DENOM, OUTSIDER, USER,
+        };
+        use crate::msg::{
+            ExecuteMsg, ListingResponse, ListingsResponse, PaymentDetails, QueryMsg, ReceiveMsg,
+            ReceiveNftMsg, RoyaltyInfoResponse, RoyaltyResponse,
+        };
+        use cosmwasm_std::{coins, to_binary, Addr, Coin, Uint128};
+        use cw20::Cw20ExecuteMsg;
+        use cw_multi_test::{App, AppResponse, Executor};
+
+        fn list_for_sale(
+            app: &mut App,
+            whoami_addr: Addr,
+            paths_addr: Addr,
+            sender: &str,
+            token_id: &str,
+            price: u128,
+        ) -> anyhow::Result<AppResponse> {
+            let msg = whoami::msg::ExecuteMsg::SendNft {
+                contract: paths_addr.to_string(),
+                token_id: token_id.to_string(),
+                msg: to_binary(&ReceiveNftMsg::ListForSale {
+                    price: Uint128::new(price),
+                })?,
+            };
+            app.execute_contract(Addr::unchecked(sender), whoami_addr, &msg, &[])
+        }
+
+        fn buy_path_native(
+            app: &mut App,
+            paths_addr: Addr,
+            sender: &str,
+            token_id: &str,
+            payment: Vec<Coin>,
+        ) -> anyhow::Result<AppResponse> {
+            let msg = ExecuteMsg::BuyPath {
+                token_id: token_id.to_string(),
+            };
+            app.execute_contract(Addr::unchecked(sender), paths_addr, &msg, &payment)
+        }
+
+        fn cancel_listing(
+            app: &mut App,
+            paths_addr: Addr,
+            sender: &str,
+            token_id: &str,
+        ) -> anyhow::Result<AppResponse> {
+            let msg = ExecuteMsg::CancelListing {
+                token_id: token_id.to_string(),
+            };
+            app.execute_contract(Addr::unchecked(sender), paths_addr, &msg, &[])
+        }
+
+        fn setup_listing(app: &mut App) -> (Addr, Addr, String) {
+            let (whoami, paths, token_id) = setup_test_case_with_name(
+                app,
+                Some(PaymentDetails::Native {
+                    denom: NATIVE_DENOM.to_string(),
+                    amount: Uint128::new(100),
+                }),
+            );
+            mint_path_native(app, paths.clone(), USER, "a", coins(100, NATIVE_DENOM)).unwrap();
+            let path = format!("{}::a", token_id);
+            let resp = list_for_sale(app, whoami.clone(), paths.clone(), USER, &path, 500).unwrap();
+            let attr = |key| event_attribute(&resp, "wasm-whoami_paths_list_path", key);
+            assert_eq!(attr("seller"), Some(USER.to_string()));
+            assert_eq!(attr("payer"), None);
+            (whoami, paths, path)
+        }
+
+        #[test]
+        fn test_list_path() {
+            let mut app = mock_app();
+            let (whoami, paths, path) = setup_listing(&mut app);
+
+            let resp = get_nft_owner(&mut app, whoami, path.clone());
+            assert_eq!(resp.owner, paths.to_string());
+
+            let resp: ListingResponse = app
+                .wrap()
+                .query_wasm_smart(
+                    paths.clone(),
+                    &QueryMsg::Listing {
+                        token_id: path.clone(),
+                    },
+                )
+                .unwrap();
+            assert_eq!(resp.seller, USER.to_string());
+            assert_eq!(
+                resp.price,
+                PaymentDetails::Native {
+                    denom: NATIVE_DENOM.to_string(),
+                    amount: Uint128::new(500),
+                }
+            );
+
+            let resp: ListingsResponse = app
+                .wrap()
+                .query_wasm_smart(
+                    paths,
+                    &QueryMsg::Listings {
+                        start_after: None,
+                        limit: None,
+                    },
+                )
+                .unwrap();
+            assert_eq!(resp.listings.len(), 1);
+            assert_eq!(resp.listings[0].token_id, path);
+        }
+
+        #[test]
+        #[should_panic(expected = "other_name was not minted through this contract")]
+        fn test_list_unknown_path() {
+            let mut app = mock_app();
+            let (whoami, paths, _path) = setup_listing(&mut app);
+
+            mint_name(&mut app, whoami.clone(), USER, "other_name").unwrap();
+            list_for_sale(&mut app, whoami, paths, USER, "other_name", 500).unwrap();
+        }
+
+        #[test]
+        fn test_buy_path_native() {
+            let mut app = mock_app();
+            let (whoami, paths, path) = setup_listing(&mut app);
+            let seller_balance = app.wrap().query_balance(USER, NATIVE_DENOM).unwrap().amount;
+
+            let err = buy_path_native(
+                &mut app,
+                paths.clone(),
+                ADMIN,
+                &path,
+                coins(400, NATIVE_DENOM),
+            )
+            .unwrap_err();
+            assert_eq!(
+                err.root_cause().to_string(),
+                "This listing costs exactly 500"
+            );
+
+            buy_path_native(
+                &mut app,
+                paths.clone(),
+                ADMIN,
+                &path,
+                coins(500, NATIVE_DENOM),
+            )
+            .unwrap();
+
+            let resp = get_nft_owner(&mut app, whoami, path.clone());
+            assert_eq!(resp.owner, ADMIN.to_string());
+            let balance = app.wrap().query_balance(USER, NATIVE_DENOM).unwrap().amount;
+            assert_eq!(balance - seller_balance, Uint128::new(500));
+
+            // The listing is gone once bought
+            let err = buy_path_native(&mut app, paths, ADMIN, &path, coins(500, NATIVE_DENOM))
+                .unwrap_err();
+            assert_eq!(
+                err.root_cause().to_string(),
+                format!("{} is not listed for sale", path)
+            );
+        }
+
+        #[test]
+        fn test_cancel_listing() {
+            let mut app = mock_app();
+            let (whoami, paths, path) = setup_listing(&mut app);
+
+            let err = cancel_listing(&mut app, paths.clone(), OUTSIDER, &path).unwrap_err();
+            assert_eq!(err.root_cause().to_string(), "Unauthorized");
+
+            cancel_listing(&mut app, paths.clone(), USER, &path).unwrap();
+            let resp = get_nft_owner(&mut app, whoami, path.clone());
+            assert_eq!(resp.owner, USER.to_string());
+
+            app.wrap()
+                .query_wasm_smart::<ListingResponse>(paths, &QueryMsg::Listing { token_id: path })
+                .unwrap_err();
+        }
+
+        #[test]
+        fn test_buy_path_cw20() {
+            let mut app = mock_app();
+            let cw20_addr = instantiate_cw20(&mut app);
+            let (whoami, paths, token_id) = setup_test_case_with_name(
+                &mut app,
+                Some(PaymentDetails::Cw20 {
+                    token_address: cw20_addr.to_string(),
+                    amount: Uint128::new(100),
+                }),
+            );
+            mint_path_cw20(
+                &mut app,
+                cw20_addr.clone(),
+                paths.clone(),
+                USER,
+                Uint128::new(100),
+                "a",
+            )
+            .unwrap();
+            let path = format!("{}::a", token_id);
+            list_for_sale(&mut app, whoami.clone(), paths.clone(), USER, &path, 500).unwrap();
+            let seller_balance = get_cw20_balance(&mut app, cw20_addr.clone(), USER);
+
+            // cw20 listings can't be bought with native funds
+            let err = buy_path_native(&mut app, paths.clone(), ADMIN, &path, vec![]).unwrap_err();
+            assert_eq!(
+                err.root_cause().to_string(),
+                "Token received is not the token configured for this contract"
+            );
+
+            let msg = Cw20ExecuteMsg::Send {
+                contract: paths.to_string(),
+                amount: Uint128::new(500),
+                msg: to_binary(&ReceiveMsg::BuyPath {
+                    token_id: path.clone(),
+                })
+                .unwrap(),
+            };
+            app.execute_contract(Addr::unchecked(ADMIN), cw20_addr.clone(), &msg, &[])
+                .unwrap();
+
+            let resp = get_nft_owner(&mut app, whoami, path);
+            assert_eq!(resp.owner, ADMIN.to_string());
+            let balance = get_cw20_balance(&mut app, cw20_addr, USER);
+            assert_eq!(balance.balance - seller_balance.balance, Uint128::new(500));
+        }
+
+        fn update_royalty(
+            app: &mut App,
+            paths_addr: Addr,
+            sender: &str,
+            royalty_bps: Option<u16>,
+        ) -> anyhow::Result<AppResponse> {
+            let msg = ExecuteMsg::UpdateRoyalty { royalty_bps };
+            app.execute_contract(Addr::unchecked(sender), paths_addr, &msg, &[])
+        }
+
+        #[test]
+        fn test_update_royalty() {
+            let mut app = mock_app();
+            let (_whoami, paths, _path) = setup_listing(&mut app);
+
+            let err = update_royalty(&mut app, paths.clone(), USER, Some(250)).unwrap_err();
+            assert_eq!(err.root_cause().to_string(), "Unauthorized");
+
+            let err = update_royalty(&mut app, paths.clone(), ADMIN, Some(10_001)).unwrap_err();
+            assert_eq!(
+                err.root_cause().to_string(),
+                "Royalty can be at most 10000 basis points"
+            );
+
+            update_royalty(&mut app, paths.clone(), ADMIN, Some(250)).unwrap();
+            let resp: RoyaltyResponse = app
+                .wrap()
+                .query_wasm_smart(paths, &QueryMsg::Royalty {})
+                .unwrap();
+            assert_eq!(resp.royalty_bps, 250);
+            assert_eq!(resp.recipient, ADMIN.to_string());
+        }
+
+        #[test]
+        fn test_royalty_info() {
+            let mut app = mock_app();
+            let (_whoami, paths, path) = setup_listing(&mut app);
+            update_royalty(&mut app, paths.clone(), ADMIN, Some(250)).unwrap();
+
+            let resp: RoyaltyInfoResponse = app
+                .wrap()
+                .query_wasm_smart(
+                    paths.clone(),
+                    &QueryMsg::RoyaltyInfo {
+                        token_id: path,
+                        sale_price: Uint128::new(1000),
+                    },
+                )
+                .unwrap();
+            assert_eq!(resp.address, ADMIN.to_string());
+            assert_eq!(resp.royalty_amount, Uint128::new(25));
+
+            // Names not minted through the contract have no royalty
+            let resp: RoyaltyInfoResponse = app
+                .wrap()
+                .query_wasm_smart(
+                    paths,
+                    &QueryMsg::RoyaltyInfo {
+                        token_id: "other_name".to_string(),
+                        sale_price: Uint128::new(1000),
+                    },
+                )
+                .unwrap();
+            assert_eq!(resp.royalty_amount, Uint128::zero());
+        }
+
+        #[test]
+        fn test_buy_path_with_royalty() {
+            let mut app = mock_app();
+            let (whoami, paths, path) = setup_listing(&mut app);
+            update_royalty(&mut app, paths.clone(), ADMIN, Some(250)).unwrap();
+            let seller_balance = app.wrap().query_balance(USER, NATIVE_DENOM).unwrap().amount;
+            let admin_balance = app
+                .wrap()
+                .query_balance(ADMIN, NATIVE_DENOM)
+                .unwrap()
+                .amount;
+
+            let res =
+                buy_path_native(&mut app, paths, ADMIN, &path, coins(500, NATIVE_DENOM)).unwrap();
+            assert_eq!(
+                event_attribute(&res, "wasm-whoami_paths_buy_path", "royalty_amount"),
+                Some("12".to_string())
+            );
+
+            let resp = get_nft_owner(&mut app, whoami, path);
+            assert_eq!(resp.owner, ADMIN.to_string());
+
+            // 2.5% of 500 rounds down to 12
+            let balance = app.wrap().query_balance(USER, NATIVE_DENOM).unwrap().amount;
+            assert_eq!(balance - seller_balance, Uint128::new(488));
+            let balance = app
+                .wrap()
+                .query_balance(ADMIN, NATIVE_DENOM)
+                .unwrap()
+                .amount;
+            assert_eq!(admin_balance - balance, Uint128::new(488));
+        }
+    }
+
+    mod returns {
+        use crate::integration_tests::tests::{
+            get_nft_owner, mint_path_native, mock_app, setup_test_case_with_name,
+            update_burn_share, withdraw_payments, ADMIN, NATIVE_DENOM, OUTSIDER, USER,
+        };
+        use crate::msg::{
+            ExecuteMsg, ListingResponse, PaymentDetails, QueryMsg, ReceiveNftMsg, ReturnPolicy,
+        };
+        use cosmwasm_std::{coins, to_binary, Addr, Decimal, Uint128};
+        use cw721::{Cw721QueryMsg, OwnerOfResponse};
+        use cw_multi_test::{App, AppResponse, Executor};
+
+        fn return_path(
+            app: &mut App,
+            whoami_addr: Addr,
+            paths_addr: Addr,
+            sender: &str,
+            token_id: &str,
+        ) -> anyhow::Result<AppResponse> {
+            let msg = whoami::msg::ExecuteMsg::SendNft {
+                contract: paths_addr.to_string(),
+                token_id: token_id.to_string(),
+                msg: to_binary(&ReceiveNftMsg::ReturnPath {})?,
+            };
+            app.execute_contract(Addr::unchecked(sender), whoami_addr, &msg, &[])
+        }
+
+        fn update_return_policy(
+            app: &mut App,
+            paths_addr: Addr,
+            sender: &str,
+            return_policy: Option<ReturnPolicy>,
+        ) -> anyhow::Result<AppResponse> {
+            let msg = ExecuteMsg::UpdateReturnPolicy { return_policy };
+            app.execute_contract(Addr::unchecked(sender), paths_addr, &msg, &[])
+        }
+
+        // Mints root_name::a for 100 with half refunded within 10 blocks
+        fn setup_returns(app: &mut App, keep_for_resale: bool) -> (Addr, Addr, String) {
+            let (whoami, paths, token_id) = setup_test_case_with_name(
+                app,
+                Some(PaymentDetails::Native {
+                    denom: NATIVE_DENOM.to_string(),
+                    amount: Uint128::new(100),
+                }),
+            );
+            update_return_policy(
+                app,
+                paths.clone(),
+                ADMIN,
+                Some(ReturnPolicy {
+                    window: 10,
+                    refund_rate: Decimal::percent(50),
+                    keep_for_resale,
+                }),
+            )
+            .unwrap();
+            mint_path_native(app, paths.clone(), USER, "a", coins(100, NATIVE_DENOM)).unwrap();
+            (whoami, paths, format!("{}::a", token_id))
+        }
+
+        #[test]
+        fn test_return_path() {
+            let mut app = mock_app();
+            let (whoami, paths, path) = setup_returns(&mut app, false);
+            let balance_before = app.wrap().query_balance(USER, NATIVE_DENOM).unwrap().amount;
+
+            return_path(&mut app, whoami.clone(), paths.clone(), USER, &path).unwrap();
+
+            let balance = app.wrap().query_balance(USER, NATIVE_DENOM).unwrap().amount;
+            assert_eq!(balance - balance_before, Uint128::new(50));
+
+            // Burned, so the path can be minted again
+            app.wrap()
+                .query_wasm_smart::<OwnerOfResponse>(
+                    whoami.clone(),
+                    &Cw721QueryMsg::OwnerOf {
+                        token_id: path.clone(),
+                        include_expired: None,
+                    },
+                )
+                .unwrap_err();
+            mint_path_native(&mut app, paths, ADMIN, "a", coins(100, NATIVE_DENOM)).unwrap();
+            let resp = get_nft_owner(&mut app, whoami, path);
+            assert_eq!(resp.owner, ADMIN.to_string());
+        }
+
+        #[test]
+        fn test_return_path_kept_for_resale() {
+            let mut app = mock_app();
+            let (whoami, paths, path) = setup_returns(&mut app, true);
+
+            return_path(&mut app, whoami.clone(), paths.clone(), USER, &path).unwrap();
+
+            let resp = get_nft_owner(&mut app, whoami, path.clone());
+            assert_eq!(resp.owner, paths.to_string());
+            let resp: ListingResponse = app
+                .wrap()
+                .query_wasm_smart(paths, &QueryMsg::Listing { token_id: path })
+                .unwrap();
+            assert_eq!(resp.seller, ADMIN.to_string());
+            assert_eq!(
+                resp.price,
+                PaymentDetails::Native {
+                    denom: NATIVE_DENOM.to_string(),
+                    amount: Uint128::new(100),
+                }
+            );
+        }
+
+        #[test]
+        fn test_refund_capped_at_amount_kept() {
+            let mut app = mock_app();
+            let (whoami, paths, token_id) = setup_test_case_with_name(
+                &mut app,
+                Some(PaymentDetails::Native {
+                    denom: NATIVE_DENOM.to_string(),
+                    amount: Uint128::new(100),
+                }),
+            );
+            update_burn_share(&mut app, paths.clone(), ADMIN, Some(Decimal::percent(40))).unwrap();
+            update_return_policy(
+                &mut app,
+                paths.clone(),
+                ADMIN,
+                Some(ReturnPolicy {
+                    window: 10,
+                    refund_rate: Decimal::one(),
+                    keep_for_resale: false,
+                }),
+            )
+            .unwrap();
+            mint_path_native(&mut app, paths.clone(), USER, "a", coins(100, NATIVE_DENOM)).unwrap();
+            let balance_before = app.wrap().query_balance(USER, NATIVE_DENOM).unwrap().amount;
+
+            // 40 was burned, so only the 60 left can come back
+            let path = format!("{}::a", token_id);
+            return_path(&mut app, whoami, paths.clone(), USER, &path).unwrap();
+            let balance = app.wrap().query_balance(USER, NATIVE_DENOM).unwrap().amount;
+            assert_eq!(balance - balance_before, Uint128::new(60));
+            let balance = app
+                .wrap()
+                .query_balance(paths, NATIVE_DENOM)
+                .unwrap()
+                .amount;
+            assert_eq!(balance, Uint128::zero());
+        }
+
+        #[test]
+        fn test_refund_after_withdrawal() {
+            let mut app = mock_app();
+            let (whoami, paths, path) = setup_returns(&mut app, false);
+
+            withdraw_payments(&mut app, paths.clone(), ADMIN).unwrap();
+            let err = return_path(&mut app, whoami, paths, USER, &path).unwrap_err();
+            assert_eq!(
+                err.root_cause().to_string(),
+                "The contract no longer holds the 50 refund"
+            );
+        }
+
+        #[test]
+        fn test_return_free_path() {
+            let mut app = mock_app();
+            let (whoami, paths, token_id) = setup_test_case_with_name(&mut app, None);
+            update_return_policy(
+                &mut app,
+                paths.clone(),
+                ADMIN,
+                Some(ReturnPolicy {
+                    window: 10,
+                    refund_rate: Decimal::percent(50),
+                    keep_for_resale: false,
+                }),
+            )
+            .unwrap();
+            mint_path_native(&mut app, paths.clone(), USER, "a", vec![]).unwrap();
+
+            let path = format!("{}::a", token_id);
+            let err = return_path(&mut app, whoami.clone(), paths, USER, &path).unwrap_err();
+            assert_eq!(
+                err.root_cause().to_string(),
+                format!("{} has nothing to refund so it can't be returned", path)
+            );
+            let resp = get_nft_owner(&mut app, whoami, path);
+            assert_eq!(resp.owner, USER.to_string());
+        }
+
+        #[test]
+        fn test_return_fully_shared_out_path() {
+            let mut app = mock_app();
+            let (whoami, paths, token_id) = setup_test_case_with_name(
+                &mut app,
+                Some(PaymentDetails::Native {
+                    denom: NATIVE_DENOM.to_string(),
+                    amount: Uint128::new(100),
+                }),
+            );
+            // Everything paid is burned, so the contract keeps nothing to refund
+            update_burn_share(&mut app, paths.clone(), ADMIN, Some(Decimal::one())).unwrap();
+            update_return_policy(
+                &mut app,
+                paths.clone(),
+                ADMIN,
+                Some(ReturnPolicy {
+                    window: 10,
+                    refund_rate: Decimal::one(),
+                    keep_for_resale: false,
+                }),
+            )
+            .unwrap();
+            mint_path_native(&mut app, paths.clone(), USER, "a", coins(100, NATIVE_DENOM)).unwrap();
+
+            let path = format!("{}::a", token_id);
+            let err = return_path(&mut app, whoami, paths, USER, &path).unwrap_err();
+            assert_eq!(
+                err.root_cause().to_string(),
+                format!("{} has nothing to refund so it can't be returned", path)
+            );
+        }
+
+        #[test]
+        fn test_return_path_window_closed() {
+            let mut app = mock_app();
+            let (whoami, paths, path) = setup_returns(&mut app, false);
+            let minted_at = app.block_info().height;
+
+            app.update_block(|block| block.height += 11);
+            let err = return_path(&mut app, whoami, paths, USER, &path).unwrap_err();
+            assert_eq!(
+                err.root_cause().to_string(),
+                format!("The return window closed at height {}", minted_at + 10)
+            );
+        }
+
+        #[test]
+        fn test_return_path_not_minter() {
+            let mut app = mock_app();
+            let (whoami, paths, path) = setup_returns(&mut app, false);
+
+            let msg = whoami::msg::ExecuteMsg::TransferNft {
+                recipient: OUTSIDER.to_string(),
+                token_id: path.clone(),
+            };
+            app.execute_contract(Addr::unchecked(USER), whoami.clone(), &msg, &[])
+                .unwrap();
+
+            let err = return_path(&mut app, whoami, paths, OUTSIDER, &path).unwrap_err();
+            assert_eq!(err.root_cause().to_string(), "Unauthorized");
+        }
+
+        #[test]
+        fn test_return_policy_updates() {
+            let mut app = mock_app();
+            let (whoami, paths, path) = setup_returns(&mut app, false);
+
+            let return_policy = ReturnPolicy {
+                window: 10,
+                refund_rate: Decimal::percent(101),
+                keep_for_resale: false,
+            };
+            let err = update_return_policy(&mut app, paths.clone(), USER, None).unwrap_err();
+            assert_eq!(err.root_cause().to_string(), "Unauthorized");
+            let err = update_return_policy(&mut app, paths.clone(), ADMIN, Some(return_policy))
+                .unwrap_err();
+            assert_eq!(
+                err.root_cause().to_string(),
+                "Refund rate must be at most 100%"
+            );
+
+            update_return_policy(&mut app, paths.clone(), ADMIN, None).unwrap();
+            let err = return_path(&mut app, whoami, paths, USER, &path).unwrap_err();
+            assert_eq!(err.root_cause().to_string(), "Paths can not be returned");
+        }
+    }
+
+    mod custody {
+        use crate::integration_tests::tests::{
+            event_attribute, get_nft_owner, mint_path_native, mock_app, setup_test_case_with_name,
+            ADMIN, OUTSIDER, USER,
+        };
+        use crate::msg::{ControllerResponse, ExecuteMsg, QueryMsg};
+        use cosmwasm_std::Addr;
+        use cw721::{Cw721QueryMsg, OwnerOfResponse};
+        use cw_multi_test::{App, AppResponse, Executor};
+
+        fn update_path_metadata(
+            app: &mut App,
+            paths_addr: Addr,
+            sender: &str,
+            token_id: &str,
+        ) -> anyhow::Result<AppResponse> {
+            let msg = ExecuteMsg::UpdatePathMetadata {
+                token_id: token_id.to_string(),
+                metadata: whoami::msg::Metadata {
+                    image: None,
+                    image_data: None,
+                    email: None,
+                    external_url: None,
+                    public_name: Some("Alice".to_string()),
+                    public_bio: None,
+                    twitter_id: None,
+                    discord_id: None,
+                    telegram_id: None,
+                    keybase_id: None,
+                    validator_operator_address: None,
+                    contract_address: None,
+                    parent_token_id: None,
+                    pgp_public_key: None,
+                },
+            };
+            app.execute_contract(Addr::unchecked(sender), paths_addr, &msg, &[])
+        }
+
+        fn update_controller(
+            app: &mut App,
+            paths_addr: Addr,
+            sender: &str,
+            token_id: &str,
+            controller: &str,
+        ) -> anyhow::Result<AppResponse> {
+            let msg = ExecuteMsg::UpdateController {
+                token_id: token_id.to_string(),
+                controller: controller.to_string(),
+            };
+            app.execute_contract(Addr::unchecked(sender), paths_addr, &msg, &[])
+        }
+
+        fn get_controller(app: &mut App, paths_addr: Addr, token_id: &str) -> ControllerResponse {
+            app.wrap()
+                .query_wasm_smart(
+                    paths_addr,
+                    &QueryMsg::Controller {
+                        token_id: token_id.to_string(),
+                    },
+                )
+                .unwrap()
+        }
+
+        // USER mints root_name::a while custodial mode is on
+        fn setup_custody(app: &mut App) -> (Addr, Addr, String) {
+            let (whoami, paths, token_id) = setup_test_case_with_name(app, None);
+            let msg = ExecuteMsg::UpdateCustodial { custodial: true };
+            app.execute_contract(Addr::unchecked(ADMIN), paths.clone(), &msg, &[])
+                .unwrap();
+            mint_path_native(app, paths.clone(), USER, "a", vec![]).unwrap();
+            (whoami, paths, format!("{}::a", token_id))
+        }
+
+        #[test]
+        fn test_custodial_mint() {
+            let mut app = mock_app();
+            let (whoami, paths, path) = setup_custody(&mut app);
+
+            let resp = get_nft_owner(&mut app, whoami, path.clone());
+            assert_eq!(resp.owner, paths.to_string());
+            let resp = get_controller(&mut app, paths.clone(), &path);
+            assert_eq!(resp.controller, USER.to_string());
+
+            // The contract is reported as holding the path, with the minter as its controller
+            let resp = mint_path_native(&mut app, paths.clone(), USER, "b", vec![]).unwrap();
+            let attr = |key| event_attribute(&resp, "wasm-whoami_paths_mint", key);
+            assert_eq!(attr("payer"), Some(USER.to_string()));
+            assert_eq!(attr("recipient"), Some(paths.to_string()));
+            assert_eq!(attr("controller"), Some(USER.to_string()));
+        }
+
+        #[test]
+        fn test_custodial_mode_admin_only() {
+            let mut app = mock_app();
+            let (_whoami, paths, _token_id) = setup_test_case_with_name(&mut app, None);
+
+            let msg = ExecuteMsg::UpdateCustodial { custodial: true };
+            let err = app
+                .execute_contract(Addr::unchecked(USER), paths, &msg, &[])
+                .unwrap_err();
+            assert_eq!(err.root_cause().to_string(), "Unauthorized");
+        }
+
+        #[test]
+        fn test_update_path_metadata() {
+            let mut app = mock_app();
+            let (_whoami, paths, path) = setup_custody(&mut app);
+
+            let err = update_path_metadata(&mut app, paths.clone(), OUTSIDER, &path).unwrap_err();
+            assert_eq!(err.root_cause().to_string(), "Unauthorized");
+            let resp = update_path_metadata(&mut app, paths, USER, &path).unwrap();
+            let attr = |key| event_attribute(&resp, "wasm-whoami_paths_update_path_metadata", key);
+            assert_eq!(attr("controller"), Some(USER.to_string()));
+            assert_eq!(attr("payer"), None);
+        }
+
+        #[test]
+        fn test_update_controller() {
+            let mut app = mock_app();
+            let (_whoami, paths, path) = setup_custody(&mut app);
+
+            let err =
+                update_controller(&mut app, paths.clone(), USER, &path, OUTSIDER).unwrap_err();
+            assert_eq!(err.root_cause().to_string(), "Unauthorized");
+
+            let resp = update_controller(&mut app, paths.clone(), ADMIN, &path, OUTSIDER).unwrap();
+            let attr = |key| event_attribute(&resp, "wasm-whoami_paths_update_controller", key);
+            assert_eq!(attr("controller"), Some(OUTSIDER.to_string()));
+            assert_eq!(attr("recipient"), None);
+            let resp = get_controller(&mut app, paths.clone(), &path);
+            assert_eq!(resp.controller, OUTSIDER.to_string());
+
+            // The old controller loses control
+            let err = update_path_metadata(&mut app, paths.clone(), USER, &path).unwrap_err();
+            assert_eq!(err.root_cause().to_string(), "Unauthorized");
+            update_path_metadata(&mut app, paths, OUTSIDER, &path).unwrap();
+        }
+
+        #[test]
+        fn test_revoke_path() {
+            let mut app = mock_app();
+            let (whoami, paths, path) = setup_custody(&mut app);
+
+            let msg = ExecuteMsg::RevokePath {
+                token_id: path.clone(),
+            };
+            let err = app
+                .execute_contract(Addr::unchecked(USER), paths.clone(), &msg, &[])
+                .unwrap_err();
+            assert_eq!(err.root_cause().to_string(), "Unauthorized");
+            app.execute_contract(Addr::unchecked(ADMIN), paths.clone(), &msg, &[])
+                .unwrap();
+
+            app.wrap()
+                .query_wasm_smart::<OwnerOfResponse>(
+                    whoami,
+                    &Cw721QueryMsg::OwnerOf {
+                        token_id: path.clone(),
+                        include_expired: None,
+                    },
+                )
+                .unwrap_err();
+            let err = update_path_metadata(&mut app, paths, USER, &path).unwrap_err();
+            assert_eq!(
+                err.root_cause().to_string(),
+                format!("{} is not held in custody", path)
+            );
+        }
+
+        #[test]
+        fn test_paths_outside_custody() {
+            let mut app = mock_app();
+            let (whoami, paths, token_id) = setup_test_case_with_name(&mut app, None);
+            mint_path_native(&mut app, paths.clone(), USER, "a", vec![]).unwrap();
+            let path = format!("{}::a", token_id);
+
+            let resp = get_nft_owner(&mut app, whoami, path.clone());
+            assert_eq!(resp.owner, USER.to_string());
+            let err = update_controller(&mut app, paths, ADMIN, &path, OUTSIDER).unwrap_err();
+            assert_eq!(
+                err.root_cause().to_string(),
+                format!("{} is not held in custody", path)
+            );
+        }
+    }
+
+    mod staking {
+        use crate::integration_tests::tests::{
+            event_attribute, get_cw20_balance, get_nft_owner, get_payment_details_balance,
+            instantiate_cw20, mint_path_cw20, mint_path_native, mock_app,
+            setup_test_case_with_name, ADMIN, NATIVE_DENOM, OUTSIDER, USER,
+        };
+        use crate::merkle::leaf_hash;
+        use crate::msg::{
+            AllowlistProof, ExecuteMsg, PaymentDetails, QueryMsg, ReceiveMsg, ReceiveNftMsg,
+            ReturnPolicy, StakesResponse, Staking, TotalStakedResponse, UsdPricing,
+        };
+        use cosmwasm_std::{coins, to_binary, Addr, Binary, Decimal, Uint128};
+        use cw20::Cw20ExecuteMsg;
+        use cw721::{Cw721QueryMsg, OwnerOfResponse};
+        use cw_multi_test::{App, AppResponse, Executor};
+
+        fn stake_for_path(
+            app: &mut App,
+            cw20_addr: Addr,
+            paths_addr: Addr,
+            amount: u128,
+            path: &str,
+        ) -> anyhow::Result<AppResponse> {
+            stake_for_path_allowlisted(app, cw20_addr, paths_addr, amount, path, None)
+        }
+
+        fn stake_for_path_allowlisted(
+            app: &mut App,
+            cw20_addr: Addr,
+            paths_addr: Addr,
+            amount: u128,
+            path: &str,
+            allowlist: Option<AllowlistProof>,
+        ) -> anyhow::Result<AppResponse> {
+            let msg = Cw20ExecuteMsg::Send {
+                contract: paths_addr.to_string(),
+                amount: Uint128::new(amount),
+                msg: to_binary(&ReceiveMsg::StakeForPath {
+                    path: path.to_string(),
+                    allowlist,
+                })?,
+            };
+            app.execute_contract(Addr::unchecked(USER), cw20_addr, &msg, &[])
+        }
+
+        fn unstake(
+            app: &mut App,
+            whoami_addr: Addr,
+            paths_addr: Addr,
+            token_id: &str,
+        ) -> anyhow::Result<AppResponse> {
+            let msg = whoami::msg::ExecuteMsg::SendNft {
+                contract: paths_addr.to_string(),
+                token_id: token_id.to_string(),
+                msg: to_binary(&ReceiveNftMsg::Unstake {})?,
+            };
+            app.execute_contract(Addr::unchecked(USER), whoami_addr, &msg, &[])
+        }
+
+        fn claim_stake(
+            app: &mut App,
+            paths_addr: Addr,
+            sender: &str,
+            token_id: &str,
+        ) -> anyhow::Result<AppResponse> {
+            let msg = ExecuteMsg::ClaimStake {
+                token_id: token_id.to_string(),
+            };
+            app.execute_contract(Addr::unchecked(sender), paths_addr, &msg, &[])
+        }
+
+        fn get_total_staked(app: &mut App, paths_addr: Addr) -> TotalStakedResponse {
+            app.wrap()
+                .query_wasm_smart(paths_addr, &QueryMsg::TotalStaked {})
+                .unwrap()
+        }
+
+        // Staking 1000 of the cw20 holds a path, unbonding over 10 blocks
+        fn setup_staking(
+            app: &mut App,
+            payment_details: Option<PaymentDetails>,
+            cw20_addr: Addr,
+            reclaim: bool,
+        ) -> (Addr, Addr, String) {
+            let (whoami, paths, token_id) = setup_test_case_with_name(app, payment_details);
+            let msg = ExecuteMsg::UpdateStaking {
+                staking: Some(Staking {
+                    token_address: cw20_addr.to_string(),
+                    amount: Uint128::new(1000),
+                    unbonding_period: 10,
+                    reclaim,
+                }),
+            };
+            app.execute_contract(Addr::unchecked(ADMIN), paths.clone(), &msg, &[])
+                .unwrap();
+            (whoami, paths, token_id)
+        }
+
+        #[test]
+        fn test_stake_for_path() {
+            let mut app = mock_app();
+            let cw20_addr = instantiate_cw20(&mut app);
+            let (whoami, paths, token_id) = setup_staking(&mut app, None, cw20_addr.clone(), false);
+            let path = format!("{}::a", token_id);
+
+            let err =
+                stake_for_path(&mut app, cw20_addr.clone(), paths.clone(), 500, "a").unwrap_err();
+            assert_eq!(
+                err.root_cause().to_string(),
+                "Staking for a path takes exactly 1000"
+            );
+
+            stake_for_path(&mut app, cw20_addr.clone(), paths.clone(), 1000, "a").unwrap();
+            let resp = get_nft_owner(&mut app, whoami, path.clone());
+            assert_eq!(resp.owner, USER.to_string());
+
+            let resp = get_total_staked(&mut app, paths.clone());
+            assert_eq!(resp.token_address, Some(cw20_addr.to_string()));
+            assert_eq!(resp.total_staked, Uint128::new(1000));
+
+            let resp: StakesResponse = app
+                .wrap()
+                .query_wasm_smart(
+                    paths,
+                    &QueryMsg::Stakes {
+                        address: USER.to_string(),
+                        start_after: None,
+                        limit: None,
+                    },
+                )
+                .unwrap();
+            assert_eq!(resp.stakes.len(), 1);
+            assert_eq!(resp.stakes[0].token_id, path);
+            assert_eq!(resp.stakes[0].amount, Uint128::new(1000));
+            assert_eq!(resp.stakes[0].unbonding_until, None);
+        }
+
+        #[test]
+        fn test_staked_path_cannot_be_returned() {
+            let mut app = mock_app();
+            let cw20_addr = instantiate_cw20(&mut app);
+            let (whoami, paths, token_id) = setup_staking(&mut app, None, cw20_addr.clone(), false);
+            let msg = ExecuteMsg::UpdateReturnPolicy {
+                return_policy: Some(ReturnPolicy {
+                    window: 10,
+                    refund_rate: Decimal::one(),
+                    keep_for_resale: false,
+                }),
+            };
+            app.execute_contract(Addr::unchecked(ADMIN), paths.clone(), &msg, &[])
+                .unwrap();
+            stake_for_path(&mut app, cw20_addr, paths.clone(), 1000, "a").unwrap();
+
+            let path = format!("{}::a", token_id);
+            let msg = whoami::msg::ExecuteMsg::SendNft {
+                contract: paths.to_string(),
+                token_id: path.clone(),
+                msg: to_binary(&ReceiveNftMsg::ReturnPath {}).unwrap(),
+            };
+            let err = app
+                .execute_contract(Addr::unchecked(USER), whoami.clone(), &msg, &[])
+                .unwrap_err();
+            assert_eq!(
+                err.root_cause().to_string(),
+                format!("{} is backed by a stake, unstake it instead", path)
+            );
+            let resp = get_nft_owner(&mut app, whoami, path);
+            assert_eq!(resp.owner, USER.to_string());
+        }
+
+        #[test]
+        fn test_stake_uses_allowlist() {
+            let mut app = mock_app();
+            let cw20_addr = instantiate_cw20(&mut app);
+            let (_whoami, paths, _token_id) =
+                setup_staking(&mut app, None, cw20_addr.clone(), false);
+            // A single leaf tree, its root is the leaf and the proof is empty
+            let msg = ExecuteMsg::UpdateAllowlistRoot {
+                root: Some(Binary::from(leaf_hash(USER, 1))),
+            };
+            app.execute_contract(Addr::unchecked(ADMIN), paths.clone(), &msg, &[])
+                .unwrap();
+
+            let err =
+                stake_for_path(&mut app, cw20_addr.clone(), paths.clone(), 1000, "a").unwrap_err();
+            assert_eq!(
+                err.root_cause().to_string(),
+                "Address is not on the allowlist"
+            );
+
+            let allowlist = AllowlistProof {
+                quota: 1,
+                proof: vec![],
+            };
+            stake_for_path_allowlisted(
+                &mut app,
+                cw20_addr.clone(),
+                paths.clone(),
+                1000,
+                "a",
+                Some(allowlist.clone()),
+            )
+            .unwrap();
+            let err =
+                stake_for_path_allowlisted(&mut app, cw20_addr, paths, 1000, "b", Some(allowlist))
+                    .unwrap_err();
+            assert_eq!(
+                err.root_cause().to_string(),
+                "Allowlist quota of 1 paths has been used up"
+            );
+        }
+
+        #[test]
+        fn test_stake_skips_oracle() {
+            let mut app = mock_app();
+            let cw20_addr = instantiate_cw20(&mut app);
+            let (whoami, paths, token_id) = setup_staking(
+                &mut app,
+                Some(PaymentDetails::Native {
+                    denom: NATIVE_DENOM.to_string(),
+                    amount: Uint128::new(100),
+                }),
+                cw20_addr.clone(),
+                false,
+            );
+            // Nothing answers at the oracle address, so any mint that quotes fails
+            let msg = ExecuteMsg::UpdateUsdPricing {
+                usd_pricing: Some(UsdPricing {
+                    usd_price: Decimal::percent(500),
+                    oracle: "oracle".to_string(),
+                    tolerance: Decimal::percent(1),
+                    max_staleness: 60,
+                }),
+            };
+            app.execute_contract(Addr::unchecked(ADMIN), paths.clone(), &msg, &[])
+                .unwrap();
+            mint_path_native(&mut app, paths.clone(), USER, "b", coins(100, NATIVE_DENOM))
+                .unwrap_err();
+
+            stake_for_path(&mut app, cw20_addr, paths, 1000, "a").unwrap();
+            let resp = get_nft_owner(&mut app, whoami, format!("{}::a", token_id));
+            assert_eq!(resp.owner, USER.to_string());
+        }
+
+        #[test]
+        fn test_unstake_and_claim() {
+            let mut app = mock_app();
+            let cw20_addr = instantiate_cw20(&mut app);
+            let (whoami, paths, token_id) = setup_staking(&mut app, None, cw20_addr.clone(), false);
+            let path = format!("{}::a", token_id);
+            let starting_balance = get_cw20_balance(&mut app, cw20_addr.clone(), USER);
+            let resp =
+                stake_for_path(&mut app, cw20_addr.clone(), paths.clone(), 1000, "a").unwrap();
+            let attr = |key| event_attribute(&resp, "wasm-whoami_paths_stake", key);
+            assert_eq!(attr("staker"), Some(USER.to_string()));
+            assert_eq!(attr("payer"), None);
+
+            let err = claim_stake(&mut app, paths.clone(), USER, &path).unwrap_err();
+            assert_eq!(
+                err.root_cause().to_string(),
+                format!("{} has not been unstaked", path)
+            );
+
+            let resp = unstake(&mut app, whoami.clone(), paths.clone(), &path).unwrap();
+            let attr = |key| event_attribute(&resp, "wasm-whoami_paths_unstake", key);
+            assert_eq!(attr("staker"), Some(USER.to_string()));
+            let unbonding_until = app.block_info().height + 10;
+            let err = claim_stake(&mut app, paths.clone(), USER, &path).unwrap_err();
+            assert_eq!(
+                err.root_cause().to_string(),
+                format!("Stake is unbonding until height {}", unbonding_until)
+            );
+
+            app.update_block(|block| block.height += 10);
+            let err = claim_stake(&mut app, paths.clone(), OUTSIDER, &path).unwrap_err();
+            assert_eq!(err.root_cause().to_string(), "Unauthorized");
+            claim_stake(&mut app, paths.clone(), USER, &path).unwrap();
+
+            let balance = get_cw20_balance(&mut app, cw20_addr, USER);
+            assert_eq!(balance.balance, starting_balance.balance);
+            let resp = get_total_staked(&mut app, paths);
+            assert_eq!(resp.total_staked, Uint128::zero());
+
+            // Burned once the stake is claimed
+            app.wrap()
+                .query_wasm_smart::<OwnerOfResponse>(
+                    whoami,
+                    &Cw721QueryMsg::OwnerOf {
+                        token_id: path,
+                        include_expired: None,
+                    },
+                )
+                .unwrap_err();
+        }
+
+        #[test]
+        fn test_claim_stake_reclaims_path() {
+            let mut app = mock_app();
+            let cw20_addr = instantiate_cw20(&mut app);
+            let (whoami, paths, token_id) = setup_staking(&mut app, None, cw20_addr.clone(), true);
+            let path = format!("{}::a", token_id);
+            stake_for_path(&mut app, cw20_addr, paths.clone(), 1000, "a").unwrap();
+
+            unstake(&mut app, whoami.clone(), paths.clone(), &path).unwrap();
+            app.update_block(|block| block.height += 10);
+            claim_stake(&mut app, paths, USER, &path).unwrap();
+
+            let resp = get_nft_owner(&mut app, whoami, path);
+            assert_eq!(resp.owner, ADMIN.to_string());
+        }
+
+        #[test]
+        fn test_stakes_not_withdrawable() {
+            let mut app = mock_app();
+            let cw20_addr = instantiate_cw20(&mut app);
+            let (_whoami, paths, _token_id) = setup_staking(
+                &mut app,
+                Some(PaymentDetails::Cw20 {
+                    token_address: cw20_addr.to_string(),
+                    amount: Uint128::new(100),
+                }),
+                cw20_addr.clone(),
+                false,
+            );
+
+            mint_path_cw20(
+                &mut app,
+                cw20_addr.clone(),
+                paths.clone(),
+                USER,
+                Uint128::new(100),
+                "a",
+            )
+            .unwrap();
+            stake_for_path(&mut app, cw20_addr, paths.clone(), 1000, "b").unwrap();
+
+            // Only the mint payment counts as collected
+            let resp = get_payment_details_balance(&mut app, paths);
+            assert_eq!(resp.amount, Uint128::new(100));
+        }
+    }
+
+    mod mint_errors {
+        use crate::contract::reply;
+        use crate::state::PENDING_MINTS;
+        use crate::ContractError;
+        use cosmwasm_std::testing::{mock_dependencies, mock_env};
+        use cosmwasm_std::{Reply, SubMsgResponse, SubMsgResult};
+
+        fn reply_with(result: SubMsgResult) -> Result<bool, ContractError> {
+            let mut deps = mock_dependencies();
+            PENDING_MINTS
+                .save(deps.as_mut().storage, 1, &"a".to_string())
+                .unwrap();
+            reply(deps.as_mut(), mock_env(), Reply { id: 1, result })?;
+            Ok(PENDING_MINTS.has(deps.as_ref().storage, 1))
+        }
+
+        #[test]
+        fn test_claimed_is_path_taken() {
+            let err = reply_with(SubMsgResult::Err(
+                "dispatch: submessages: token_id already claimed".to_string(),
+            ))
+            .unwrap_err();
+            assert_eq!(err.to_string(), "Path a is already taken");
+        }
+
+        #[test]
+        fn test_other_errors_are_mint_failed() {
+            // Loosely similar wording must not be mistaken for a taken or invalid path
+            for error in ["Unauthorized", "invalid metadata", "already expired"] {
+                let err = reply_with(SubMsgResult::Err(error.to_string())).unwrap_err();
+                assert_eq!(err.to_string(), format!("Failed to mint path a: {}", error));
+            }
+        }
+
+        #[test]
+        fn test_success_clears_pending_mint() {
+            let pending = reply_with(SubMsgResult::Ok(SubMsgResponse {
+                events: vec![],
+                data: None,
+            }))
+            .unwrap();
+            assert!(!pending);
+        }
+    }
 }
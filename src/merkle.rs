@@ -0,0 +1,23 @@
+use cosmwasm_std::Binary;
+use sha2::{Digest, Sha256};
+
+// Leaves commit to an address and how many paths it may mint
+pub fn leaf_hash(address: &str, quota: u32) -> Vec<u8> {
+    Sha256::digest(format!("{}:{}", address, quota).as_bytes()).to_vec()
+}
+
+// Pairs are hashed smallest first so proofs don't need to say which side each sibling is on
+pub fn hash_pair(a: &[u8], b: &[u8]) -> Vec<u8> {
+    let (first, second) = if a <= b { (a, b) } else { (b, a) };
+    let mut hasher = Sha256::new();
+    hasher.update(first);
+    hasher.update(second);
+    hasher.finalize().to_vec()
+}
+
+pub fn verify_proof(root: &[u8], leaf: &[u8], proof: &[Binary]) -> bool {
+    let computed = proof
+        .iter()
+        .fold(leaf.to_vec(), |hash, sibling| hash_pair(&hash, sibling));
+    computed == root
+}
@@ -1,6 +1,9 @@
-use cosmwasm_std::Uint128;
+use crate::state::{PlatformFeeConfig, StakingConfig, UsdPricingConfig};
+use cosmwasm_std::{Binary, Decimal, Timestamp, Uint128};
+use cw2::ContractVersion;
 use cw20::Cw20ReceiveMsg;
 use cw721::Cw721ReceiveMsg;
+use cw_utils::Expiration;
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 
@@ -17,17 +20,184 @@ pub enum PaymentDetails {
     },
 }
 
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum HolderRule {
+    Cw20Balance {
+        token_address: String,
+        min_balance: Uint128,
+    },
+    Cw721Ownership {
+        collection_address: String,
+    },
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct HolderDiscount {
+    pub priority: u32, // Only the highest priority discount the minter qualifies for is applied
+    pub rule: HolderRule,
+    pub discount: Decimal, // Fraction of the price taken off, e.g. 0.1 for 10%
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct BundleDiscount {
+    pub min_quantity: u32, // Applies to batches of at least this many paths
+    pub discount: Decimal, // Fraction taken off every path in the batch
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum ReferralPayout {
+    Forward, // Sent to the referrer as part of the mint
+    Accrue,  // Credited to the referrer to claim later
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct ReferralConfig {
+    pub share: Decimal, // Fraction of the mint payment paid to the referrer
+    pub payout: ReferralPayout,
+}
+
+// How long after minting a path can be sent back, and how much of its price comes with it
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct ReturnPolicy {
+    pub window: u64,           // In blocks
+    pub refund_rate: Decimal,  // Fraction of the recorded mint price refunded
+    pub keep_for_resale: bool, // Returned paths are listed by the admin rather than burned
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct PathPrice {
+    pub path: String,
+    pub payment_details: PaymentDetails,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum PathPattern {
+    Prefix { value: String },
+    Suffix { value: String },
+    Contains { value: String },
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct PatternPrice {
+    pub pattern: PathPattern,
+    pub payment_details: PaymentDetails,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct PlatformFee {
+    pub fee_collector: String, // Only the fee collector can change the platform fee
+    pub rate: Decimal,         // Fraction of each mint payment sent to the fee collector
+}
+
+// Signed off-chain by the voucher signer, the signature is over the sha256 of this as JSON
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct Voucher {
+    // Ties the signature to this contract on this chain so it can't be replayed elsewhere
+    pub contract_address: String,
+    pub chain_id: String,
+    pub recipient: String,
+    pub path: Option<String>, // Any path when not set
+    pub price: Uint128,       // In the path's payment asset, zero for a free path
+    pub expiry: Expiration,
+    pub nonce: u64, // Each nonce can only be redeemed once
+}
+
+// Proves the minter's leaf, sha256("{address}:{quota}"), is in the allowlist tree
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct AllowlistProof {
+    pub quota: u32, // Paths the address can mint across every allowlisted mint
+    pub proof: Vec<Binary>,
+}
+
+// In blocks counted from the commit
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct CommitWindow {
+    pub min_delay: u64, // A commitment can be revealed this many blocks after it is made
+    pub expiry: u64,    // and no later than this many
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct UsdPricing {
+    pub usd_price: Decimal, // Replaces the base payment amount, paid in the same asset
+    pub oracle: String,
+    pub tolerance: Decimal, // How far the amount sent can be from the quote as prices move
+    pub max_staleness: u64, // Seconds an oracle price can go without an update
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct Staking {
+    pub token_address: String,
+    pub amount: Uint128,       // Locked for as long as the path is held
+    pub unbonding_period: u64, // In blocks
+    pub reclaim: bool,         // Unstaked paths go to the admin rather than being burned
+}
+
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
 pub struct InstantiateMsg {
     pub admin: String,          // Only the admin can withdraw the name if needed
     pub whoami_address: String, // Address of base whoami contract
     pub payment_details: Option<PaymentDetails>, // Users may have to pay in a cw20 or a native token
+    pub platform_fee: Option<PlatformFee>,
 }
 
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct MigrateMsg {}
+
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
 #[serde(rename_all = "snake_case")]
 pub enum ReceiveMsg {
-    MintPath { path: String },
+    MintPath {
+        path: String,
+        referrer: Option<String>,
+        parent: Option<String>, // A registered sub-root to mint under instead of the root
+        max_price: Option<Uint128>, // Fails if the price is higher, anything sent over the price is refunded
+        allowlist: Option<AllowlistProof>, // Needed under the root while an allowlist root is set
+    },
+    MintPaths {
+        paths: Vec<String>,
+        referrer: Option<String>,
+        parent: Option<String>,
+        max_price: Option<Uint128>, // For the whole batch
+        allowlist: Option<AllowlistProof>,
+    }, // The amount sent must cover every path after any bundle discount
+    RevealPath {
+        path: String,
+        salt: String,
+        referrer: Option<String>,
+        parent: Option<String>,
+        max_price: Option<Uint128>,
+        allowlist: Option<AllowlistProof>,
+    }, // Mints a path committed to earlier with CommitPath
+    MintWithVoucher {
+        path: String,
+        voucher: Voucher,
+        signature: Binary,
+    },
+    BuyPath {
+        token_id: String,
+    }, // For listings priced in this cw20
+    StakeForPath {
+        path: String,
+        allowlist: Option<AllowlistProof>,
+    }, // Locks the staking amount instead of paying for the path
+}
+
+// Sent with a path minted through this contract to let others mint under it
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum ReceiveNftMsg {
+    RegisterSubRoot {
+        payment_details: Option<PaymentDetails>, // Paid to the sub-root owner, less any platform fee
+        gating_rules: Vec<HolderRule>,
+    },
+    ListForSale {
+        price: Uint128, // In the configured payment asset
+    }, // Held by this contract until it is bought or the listing is cancelled
+    ReturnPath {}, // Only the minter, within the return window
+    Unstake {},    // Starts unbonding the stake behind the path
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
@@ -35,10 +205,163 @@ pub enum ReceiveMsg {
 pub enum ExecuteMsg {
     Receive(Cw20ReceiveMsg),
     ReceiveNft(Cw721ReceiveMsg),
-    MintPath { path: String }, // Equivalent to receive but with no cost
-    UpdateAdmin { new_admin: String },
+    MintPath {
+        path: String,
+        referrer: Option<String>,
+        parent: Option<String>,
+        max_price: Option<Uint128>,
+        allowlist: Option<AllowlistProof>,
+    }, // Paid with native funds, or for cw20 prices through an allowance this contract spends
+    MintPaths {
+        paths: Vec<String>,
+        referrer: Option<String>,
+        parent: Option<String>,
+        max_price: Option<Uint128>,
+        allowlist: Option<AllowlistProof>,
+    },
+    CommitPath {
+        commitment: Binary, // sha256 of "{path}:{sender}:{salt}"
+    },
+    RevealPath {
+        path: String,
+        salt: String,
+        referrer: Option<String>,
+        parent: Option<String>,
+        max_price: Option<Uint128>,
+        allowlist: Option<AllowlistProof>,
+    },
+    UpdateCommitWindow {
+        commit_window: CommitWindow,
+    },
+    UpdateRequireCommitment {
+        require_commitment: bool, // MintPath, MintPaths and StakeForPath are rejected while set
+    },
+    MintWithVoucher {
+        path: String,
+        voucher: Voucher,
+        signature: Binary,
+    }, // Skips gating and any discounts, the voucher sets the price
+    UpdateVoucherSigner {
+        pubkey: Option<Binary>, // secp256k1 public key, vouchers are disabled when not set
+    },
+    UpdateAllowlistRoot {
+        root: Option<Binary>, // Anyone can mint under the root again when not set
+    },
+    UpdateUsdPricing {
+        usd_pricing: Option<UsdPricing>, // Exact and pattern path prices are unaffected
+    },
+    BuyPath {
+        token_id: String,
+    }, // For listings priced in a native token
+    CancelListing {
+        token_id: String,
+    }, // Hands the path back to the seller
+    UpdateRoyalty {
+        royalty_bps: Option<u16>, // Taken from each resale and paid to the admin
+    },
+    UpdateReturnPolicy {
+        return_policy: Option<ReturnPolicy>,
+    },
+    UpdateCustodial {
+        custodial: bool, // Paths minted while set stay with this contract under a controller
+    },
+    UpdatePathMetadata {
+        token_id: String,
+        metadata: whoami::msg::Metadata, // The parent token id is always kept
+    }, // Controller only
+    UpdateController {
+        token_id: String,
+        controller: String,
+    }, // Admin only
+    RevokePath {
+        token_id: String,
+    }, // Admin only, burns the path and returns any stake
+    UpdateStaking {
+        staking: Option<Staking>,
+    },
+    Unstake {
+        token_id: String,
+    }, // For staked paths held in custody, others are sent back with an Unstake hook
+    ClaimStake {
+        token_id: String,
+    }, // Once unbonded
+    UpdateAdmin {
+        new_admin: String,
+    },
     WithdrawPayments {},
     WithdrawRootToken {},
+    UpdateGatingRules {
+        rules: Vec<HolderRule>,
+    }, // Minters must satisfy at least one rule
+    UpdateDiscounts {
+        discounts: Vec<HolderDiscount>,
+    },
+    UpdateBundleDiscounts {
+        bundle_discounts: Vec<BundleDiscount>,
+    }, // Replaces the existing schedule
+    UpdateReferralConfig {
+        referral_config: Option<ReferralConfig>,
+    },
+    ClaimReferralRewards {},
+    UpdateBurnShare {
+        burn_share: Option<Decimal>,
+    }, // Fraction of each mint payment burned
+    UpdatePlatformFee {
+        fee_collector: String,
+        rate: Decimal,
+    },
+    UpdateReservedPaths {
+        add: Vec<String>,
+        remove: Vec<String>,
+    }, // Reserved paths can only be minted by the admin
+    UpdatePathPrices {
+        set: Vec<PathPrice>,
+        remove: Vec<String>,
+    }, // Exact path prices, checked before any pattern
+    UpdatePatternPrices {
+        patterns: Vec<PatternPrice>,
+    }, // Replaces the existing patterns, the first match applies
+    UpdateSubRoot {
+        token_id: String,
+        payment_details: Option<PaymentDetails>,
+        gating_rules: Vec<HolderRule>,
+    },
+    WithdrawSubRoot {
+        token_id: String,
+    }, // Hands the path back to its owner
+    AddHook {
+        addr: String,
+    },
+    RemoveHook {
+        addr: String,
+    },
+}
+
+// Queried on the oracle contract set for USD pricing
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum OracleQueryMsg {
+    Price { asset: String }, // A native denom or cw20 contract address
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub struct OraclePriceResponse {
+    pub price: Decimal, // USD for one unit of the asset's smallest denomination, e.g. one ujuno
+    pub updated_at: Timestamp,
+}
+
+// Sent to every hook contract after a path is minted
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum HookExecuteMsg {
+    PathMintedHook {
+        root: String,
+        path: String,
+        owner: String,              // This contract for paths minted into custody
+        controller: Option<String>, // Set for paths minted into custody
+        amount: Uint128,            // In the payment asset, zero for free mints
+    },
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
@@ -47,6 +370,72 @@ pub enum QueryMsg {
     Config {},
     PaymentDetails {},
     PaymentDetailsBalance {},
+    GatingRules {},
+    CanMint {
+        address: String,
+    },
+    Discounts {},
+    QuotePrice {
+        address: String,
+        path: String,
+    },
+    BundleDiscounts {},
+    QuotePaths {
+        address: String,
+        paths: Vec<String>,
+    },
+    ReferralConfig {},
+    ReferrerStats {
+        address: String,
+    },
+    Burned {},
+    PlatformFee {},
+    PathAvailable {
+        path: String,
+    },
+    Info {}, // Everything a mint page needs in one query
+    CommitWindow {},
+    VoucherSigner {},
+    AllowlistRoot {},
+    UsdPricing {},
+    Royalty {},
+    ReturnPolicy {},
+    Custodial {},
+    Staking {},
+    TotalStaked {},
+    Stakes {
+        address: String,
+        start_after: Option<String>,
+        limit: Option<u32>,
+    },
+    Controller {
+        token_id: String,
+    },
+    // Follows cw2981 so other marketplaces can pay the royalty too. The whole royalty goes to
+    // the admin, it isn't shared out like mint payments are
+    RoyaltyInfo {
+        token_id: String,
+        sale_price: Uint128,
+    },
+    Listing {
+        token_id: String,
+    },
+    Listings {
+        start_after: Option<String>,
+        limit: Option<u32>,
+    },
+    AllowlistUsage {
+        address: String,
+        quota: u32,
+    },
+    Hooks {},
+    SubRoot {
+        token_id: String,
+    },
+    PathPrices {
+        start_after: Option<String>,
+        limit: Option<u32>,
+    },
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
@@ -61,3 +450,241 @@ pub struct PaymentDetailsBalanceResponse {
     pub payment_details: Option<PaymentDetails>,
     pub amount: Uint128,
 }
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub struct GatingRulesResponse {
+    pub rules: Vec<HolderRule>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub struct HolderRuleFailure {
+    pub rule: HolderRule,
+    pub reason: String,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub struct CanMintResponse {
+    pub can_mint: bool,
+    pub failed_rules: Vec<HolderRuleFailure>, // Empty when the address can mint
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub struct DiscountsResponse {
+    pub discounts: Vec<HolderDiscount>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub struct BundleDiscountsResponse {
+    pub bundle_discounts: Vec<BundleDiscount>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub struct QuotePriceResponse {
+    pub payment_details: Option<PaymentDetails>,
+    pub base_amount: Uint128,
+    pub discount: Option<Decimal>,
+    pub amount: Uint128,  // What the address has to pay after any discount
+    pub usd_priced: bool, // From the USD price, so it can move with the oracle until minted
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub struct QuotePathsResponse {
+    pub quotes: Vec<QuotePriceResponse>, // Per path, with the bundle discount already taken off
+    pub bundle_discount: Option<Decimal>,
+    pub amount: Uint128, // Total to send for the whole batch
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub struct ReferralConfigResponse {
+    pub referral_config: Option<ReferralConfig>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub struct ReferrerStatsResponse {
+    pub address: String,
+    pub referrals: u64,
+    pub total_earned: Uint128,
+    pub claimable: Uint128, // Rewards accrued but not yet claimed
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub struct BurnedResponse {
+    pub burn_share: Option<Decimal>,
+    pub total_burned: Uint128, // In the payment asset
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub struct PlatformFeeResponse {
+    pub platform_fee: Option<PlatformFeeConfig>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub struct CommitWindowResponse {
+    pub commit_window: CommitWindow,
+    pub require_commitment: bool,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub struct VoucherSignerResponse {
+    pub pubkey: Option<Binary>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub struct ListingResponse {
+    pub token_id: String,
+    pub seller: String,
+    pub price: PaymentDetails,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub struct ListingsResponse {
+    pub listings: Vec<ListingResponse>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub struct RoyaltyResponse {
+    pub royalty_bps: u16,
+    pub recipient: String,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub struct RoyaltyInfoResponse {
+    pub address: String,
+    pub royalty_amount: Uint128,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub struct StakingResponse {
+    pub staking: Option<StakingConfig>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub struct TotalStakedResponse {
+    pub token_address: Option<String>, // The staking token currently configured
+    pub total_staked: Uint128,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub struct StakeResponse {
+    pub token_id: String,
+    pub token_address: String,
+    pub amount: Uint128,
+    pub unbonding_until: Option<u64>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub struct StakesResponse {
+    pub stakes: Vec<StakeResponse>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub struct CustodialResponse {
+    pub custodial: bool,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub struct ControllerResponse {
+    pub token_id: String,
+    pub controller: String,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub struct ReturnPolicyResponse {
+    pub return_policy: Option<ReturnPolicy>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub struct UsdPricingResponse {
+    pub usd_pricing: Option<UsdPricingConfig>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub struct AllowlistRootResponse {
+    pub root: Option<Binary>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub struct AllowlistUsageResponse {
+    pub used: u32,
+    pub remaining: u32,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum PathStatus {
+    Available,
+    Taken { owner: String },
+    Reserved,
+    Invalid { reason: String },
+    NoRoot,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub struct PathAvailableResponse {
+    pub token_id: Option<String>, // The full whoami token id the path would be minted as
+    pub status: PathStatus,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub struct PathPricesResponse {
+    pub prices: Vec<PathPrice>,
+    pub patterns: Vec<PatternPrice>, // Always returned in full
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub struct SubRootResponse {
+    pub token_id: String,
+    pub owner: String,
+    pub payment_details: Option<PaymentDetails>,
+    pub gating_rules: Vec<HolderRule>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub struct HooksResponse {
+    pub hooks: Vec<String>,
+}
+
+// There is no pause or mint schedule, paths can be minted whenever token_id is set
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub struct InfoResponse {
+    pub token_id: Option<String>, // The root name paths are minted off, if one has been received
+    pub whoami_address: String,
+    pub admin: String,
+    pub payment_details: Option<PaymentDetails>, // The payment mode and base price, None if minting is free
+    pub current_price: Option<PaymentDetails>, // What a path without a price override costs now, before discounts
+    pub collected_balance: Uint128,            // Available to WithdrawPayments
+    pub minted_count: u64,
+    pub contract_version: ContractVersion,
+}
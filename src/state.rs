@@ -1,9 +1,12 @@
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 
-use crate::msg::PaymentDetails;
-use cosmwasm_std::Addr;
-use cw_storage_plus::Item;
+use crate::msg::{
+    BundleDiscount, CommitWindow, HolderDiscount, HolderRule, PatternPrice, PaymentDetails,
+    ReferralConfig, ReturnPolicy,
+};
+use cosmwasm_std::{Addr, Binary, Decimal, Empty, Uint128};
+use cw_storage_plus::{Item, Map};
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
 pub struct Config {
@@ -12,5 +15,122 @@ pub struct Config {
     pub token_id: Option<String>, // If we have received a name to mint paths off this will be the token_id
 }
 
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema, Default)]
+pub struct ReferrerStats {
+    pub referrals: u64,
+    pub total_earned: Uint128,
+    pub claimable: Uint128,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct PlatformFeeConfig {
+    pub fee_collector: Addr,
+    pub rate: Decimal,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct UsdPricingConfig {
+    pub usd_price: Decimal,
+    pub oracle: Addr,
+    pub tolerance: Decimal,
+    pub max_staleness: u64,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct StakingConfig {
+    pub token_address: Addr,
+    pub amount: Uint128,
+    pub unbonding_period: u64,
+    pub reclaim: bool,
+}
+
+// Tokens locked behind a path, kept in the token they were staked in
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct Stake {
+    pub staker: Addr,
+    pub token_address: Addr,
+    pub amount: Uint128,
+    pub unbonding_until: Option<u64>, // Height the stake can be claimed from once unstaked
+}
+
+// A path held by this contract until it is bought
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct Listing {
+    pub seller: Addr,
+    pub price: PaymentDetails, // Fixed when listed, later payment changes don't affect it
+}
+
+// Every path minted through this contract
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct MintRecord {
+    pub minter: Addr,
+    pub parent: String, // Token id the path was minted under
+    // Records from before returns were added default to no height, price or kept amount
+    #[serde(default)]
+    pub height: u64,
+    #[serde(default)]
+    pub price: Option<PaymentDetails>, // Amount paid to the contract, unset under sub-roots
+    #[serde(default)]
+    pub kept: Uint128, // What stayed after the platform fee, referral reward and burn
+}
+
+// A path held by this contract that others can mint under, priced and gated by its owner
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct SubRoot {
+    pub owner: Addr,
+    pub payment_details: Option<PaymentDetails>,
+    pub gating_rules: Vec<HolderRule>,
+}
+
 pub const CONFIG: Item<Config> = Item::new("config");
 pub const PAYMENT_DETAILS: Item<PaymentDetails> = Item::new("payment_details");
+// Price overrides, always in the same asset as PAYMENT_DETAILS
+pub const PATH_PRICES: Map<&str, PaymentDetails> = Map::new("path_prices");
+pub const PATTERN_PRICES: Item<Vec<PatternPrice>> = Item::new("pattern_prices");
+pub const GATING_RULES: Item<Vec<HolderRule>> = Item::new("gating_rules");
+pub const DISCOUNTS: Item<Vec<HolderDiscount>> = Item::new("discounts");
+// Largest minimum quantity first
+pub const BUNDLE_DISCOUNTS: Item<Vec<BundleDiscount>> = Item::new("bundle_discounts");
+pub const REFERRAL_CONFIG: Item<ReferralConfig> = Item::new("referral_config");
+pub const REFERRER_STATS: Map<&Addr, ReferrerStats> = Map::new("referrer_stats");
+// Sum of unclaimed referral rewards, held back from WithdrawPayments
+pub const REFERRAL_REWARDS_OWED: Item<Uint128> = Item::new("referral_rewards_owed");
+pub const BURN_SHARE: Item<Decimal> = Item::new("burn_share");
+pub const TOTAL_BURNED: Item<Uint128> = Item::new("total_burned");
+pub const PLATFORM_FEE: Item<PlatformFeeConfig> = Item::new("platform_fee");
+// The path each in-flight whoami mint is for keyed by its reply id, cleared again in the reply
+pub const PENDING_MINTS: Map<u64, String> = Map::new("pending_mints");
+pub const RESERVED_PATHS: Map<&str, Empty> = Map::new("reserved_paths");
+// Contracts sent a PathMintedHook after every mint
+pub const HOOKS: Item<Vec<Addr>> = Item::new("hooks");
+// Paths minted since this was introduced in 1.1.0, earlier mints are not counted
+pub const MINTED_COUNT: Item<u64> = Item::new("minted_count");
+// Keyed by full token id
+pub const MINT_RECORDS: Map<&str, MintRecord> = Map::new("mint_records");
+pub const SUB_ROOTS: Map<&str, SubRoot> = Map::new("sub_roots");
+pub const COMMIT_WINDOW: Item<CommitWindow> = Item::new("commit_window");
+// While set paths can only be minted by revealing a commitment
+pub const REQUIRE_COMMITMENT: Item<bool> = Item::new("require_commitment");
+// Block height each commitment was made at, keyed by the committer and the commitment hash
+pub const COMMITMENTS: Map<(&Addr, &[u8]), u64> = Map::new("commitments");
+pub const VOUCHER_SIGNER: Item<Binary> = Item::new("voucher_signer");
+pub const USED_VOUCHER_NONCES: Map<u64, Empty> = Map::new("used_voucher_nonces");
+pub const ALLOWLIST_ROOT: Item<Binary> = Item::new("allowlist_root");
+pub const USD_PRICING: Item<UsdPricingConfig> = Item::new("usd_pricing");
+// Keyed by full token id
+pub const LISTINGS: Map<&str, Listing> = Map::new("listings");
+// Basis points of each resale paid to the admin
+pub const ROYALTY_BPS: Item<u16> = Item::new("royalty_bps");
+pub const RETURN_POLICY: Item<ReturnPolicy> = Item::new("return_policy");
+pub const CUSTODIAL: Item<bool> = Item::new("custodial");
+// Who manages each path held in custody, keyed by full token id
+pub const CONTROLLERS: Map<&str, Addr> = Map::new("controllers");
+pub const STAKING: Item<StakingConfig> = Item::new("staking");
+// Keyed by full token id
+pub const STAKES: Map<&str, Stake> = Map::new("stakes");
+// Token ids staked for by each address
+pub const STAKER_PATHS: Map<(&Addr, &str), Empty> = Map::new("staker_paths");
+// Keyed by cw20 address, never counted as collected payments
+pub const TOTAL_STAKED: Map<&str, Uint128> = Map::new("total_staked");
+// Paths minted against each allowlist leaf, keyed by the leaf hash
+pub const ALLOWLIST_USAGE: Map<&[u8], u32> = Map::new("allowlist_usage");